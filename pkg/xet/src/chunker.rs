@@ -0,0 +1,243 @@
+// Content-defined chunking for the upload-side dedup path: splits a file's
+// bytes on a rolling Gear hash instead of fixed-size boundaries, so inserting
+// or removing bytes anywhere in the file only changes the chunks touching
+// the edit, not every chunk after it. Modeled on proxmox-backup's
+// `chunker`/`chunk_stream` and its `merge_known_chunks` coalescing step;
+// `hf_adapter.rs`'s `upload_via_lfs_batch` is the only caller.
+use once_cell::sync::Lazy;
+
+/// Bounds and sensitivity for [`chunk_stream`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ChunkerConfig {
+    pub(crate) min_size: usize,
+    pub(crate) max_size: usize,
+    /// A boundary is declared wherever `(hash & mask) == mask`; a wider mask
+    /// (more set bits) means rarer boundaries and a larger average chunk.
+    pub(crate) mask: u64,
+}
+
+impl Default for ChunkerConfig {
+    /// Targets an average chunk size around 64KiB: a 16-bit mask fires
+    /// roughly once every 2^16 bytes once the minimum is cleared.
+    fn default() -> Self {
+        Self {
+            min_size: 16 * 1024,
+            max_size: 1024 * 1024,
+            mask: (1u64 << 16) - 1,
+        }
+    }
+}
+
+/// One content-defined chunk: its byte range within the source buffer and
+/// its blake3 hash (used as the chunk's oid for dedup lookups).
+#[derive(Debug, Clone)]
+pub(crate) struct Chunk {
+    pub(crate) offset: usize,
+    pub(crate) len: usize,
+    pub(crate) hash_hex: String,
+}
+
+/// Split `data` into content-defined chunks per `config`, hashing each with
+/// blake3. Empty input yields no chunks.
+pub(crate) fn chunk_stream(data: &[u8], config: &ChunkerConfig) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut start = 0usize;
+    let mut hash: u64 = 0;
+
+    for i in 0..data.len() {
+        hash = (hash << 1).wrapping_add(GEAR[data[i] as usize]);
+        let chunk_len = i + 1 - start;
+
+        let hit_max = chunk_len >= config.max_size;
+        let boundary = chunk_len >= config.min_size && (hash & config.mask) == config.mask;
+
+        if hit_max || boundary {
+            chunks.push(make_chunk(data, start, i + 1));
+            start = i + 1;
+            hash = 0;
+        }
+    }
+
+    if start < data.len() {
+        chunks.push(make_chunk(data, start, data.len()));
+    }
+
+    chunks
+}
+
+fn make_chunk(data: &[u8], start: usize, end: usize) -> Chunk {
+    let hash_hex = blake3::hash(&data[start..end]).to_hex().to_string();
+    Chunk {
+        offset: start,
+        len: end - start,
+        hash_hex,
+    }
+}
+
+/// A run of consecutive chunks collapsed into a single byte range, tagged by
+/// whether the remote already has every chunk in that range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MergedRange {
+    Known { offset: usize, len: usize },
+    Unknown { offset: usize, len: usize },
+}
+
+/// Coalesce runs of consecutive chunks sharing the same known/unknown status
+/// (per `known[i]`, aligned with `chunks[i]`) into single ranges, so a caller
+/// can skip or upload a whole run at once instead of chunk-by-chunk.
+pub(crate) fn merge_known_chunks(chunks: &[Chunk], known: &[bool]) -> Vec<MergedRange> {
+    assert_eq!(chunks.len(), known.len(), "chunks and known must be parallel");
+
+    let mut merged: Vec<MergedRange> = Vec::new();
+    for (chunk, &is_known) in chunks.iter().zip(known) {
+        match merged.last_mut() {
+            Some(MergedRange::Known { len, .. }) if is_known => *len += chunk.len,
+            Some(MergedRange::Unknown { len, .. }) if !is_known => *len += chunk.len,
+            _ => merged.push(if is_known {
+                MergedRange::Known {
+                    offset: chunk.offset,
+                    len: chunk.len,
+                }
+            } else {
+                MergedRange::Unknown {
+                    offset: chunk.offset,
+                    len: chunk.len,
+                }
+            }),
+        }
+    }
+    merged
+}
+
+/// Precomputed Gear-hash table: one pseudo-random `u64` per byte value,
+/// generated deterministically at first use via splitmix64 so the table
+/// doesn't need 256 magic constants typed out by hand.
+static GEAR: Lazy<[u64; 256]> = Lazy::new(|| {
+    let mut table = [0u64; 256];
+    let mut state = 0x9e3779b97f4a7c15u64;
+    for slot in table.iter_mut() {
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        *slot = z ^ (z >> 31);
+    }
+    table
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_input_yields_no_chunks() {
+        assert!(chunk_stream(&[], &ChunkerConfig::default()).is_empty());
+    }
+
+    /// Deterministic xorshift64 bytes: unlike a short repeating pattern,
+    /// these give the rolling hash real content to find boundaries in.
+    fn pseudo_random_bytes(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chunks_cover_the_whole_input_contiguously() {
+        let data = pseudo_random_bytes(200_000);
+        let config = ChunkerConfig::default();
+        let chunks = chunk_stream(&data, &config);
+
+        assert!(chunks.len() > 1, "expected more than one chunk for 200KB of varied data");
+        let mut expected_offset = 0;
+        for chunk in &chunks {
+            assert_eq!(chunk.offset, expected_offset);
+            assert!(chunk.len <= config.max_size);
+            expected_offset += chunk.len;
+        }
+        assert_eq!(expected_offset, data.len());
+    }
+
+    #[test]
+    fn forces_a_cut_at_max_size_when_no_boundary_fires() {
+        // A mask this wide almost never fires on its own within max_size, so
+        // every chunk but the last should be forced by the max-size cap.
+        let config = ChunkerConfig {
+            min_size: 64,
+            max_size: 1024,
+            mask: u64::MAX,
+        };
+        let data = vec![0x42u8; 4096];
+        let chunks = chunk_stream(&data, &config);
+
+        assert!(chunks.len() >= 4);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert_eq!(chunk.len, config.max_size);
+        }
+    }
+
+    #[test]
+    fn an_edit_only_perturbs_chunks_touching_it() {
+        let data = pseudo_random_bytes(300_000);
+        let config = ChunkerConfig::default();
+        let before = chunk_stream(&data, &config);
+
+        // Insert (not flip) a byte past the first chunk boundary, so every
+        // byte after it shifts to a new absolute offset. A fixed-size
+        // chunker would now misalign every chunk from here to the end of the
+        // file; the CDC resync property this chunker exists for means the
+        // rolling hash should instead pick back up the same boundaries
+        // relative to content within a chunk or two of the edit, so the long
+        // tail of chunk hashes matches again even though their offsets
+        // don't.
+        let mut edited = data.clone();
+        let edit_at = edited.len() / 2;
+        edited.insert(edit_at, 0xAB);
+        let after = chunk_stream(&edited, &config);
+
+        let before_hashes: Vec<&str> = before.iter().map(|c| c.hash_hex.as_str()).collect();
+        let after_hashes: Vec<&str> = after.iter().map(|c| c.hash_hex.as_str()).collect();
+
+        let resynced_suffix_len = before_hashes
+            .iter()
+            .rev()
+            .zip(after_hashes.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        assert!(
+            resynced_suffix_len > before_hashes.len() / 2,
+            "expected most chunks after the edit to resync by content, but only the \
+             last {resynced_suffix_len} of {} chunks matched (fixed-size boundaries \
+             would resync none of them)",
+            before_hashes.len(),
+        );
+    }
+
+    #[test]
+    fn merge_known_chunks_coalesces_consecutive_runs() {
+        let chunks = vec![
+            Chunk { offset: 0, len: 10, hash_hex: "a".into() },
+            Chunk { offset: 10, len: 10, hash_hex: "b".into() },
+            Chunk { offset: 20, len: 10, hash_hex: "c".into() },
+            Chunk { offset: 30, len: 10, hash_hex: "d".into() },
+        ];
+        let known = [true, true, false, true];
+        let merged = merge_known_chunks(&chunks, &known);
+
+        assert_eq!(
+            merged,
+            vec![
+                MergedRange::Known { offset: 0, len: 20 },
+                MergedRange::Unknown { offset: 20, len: 10 },
+                MergedRange::Known { offset: 30, len: 10 },
+            ]
+        );
+    }
+}
@@ -38,9 +38,27 @@ impl XetError {
     }
 
     pub fn from_anyhow(err: anyhow::Error) -> *mut XetError {
+        let code = if err
+            .downcast_ref::<crate::integrity::ChecksumMismatchError>()
+            .is_some()
+        {
+            XetErrorCode::ChecksumMismatch
+        } else if err
+            .downcast_ref::<crate::network_policy::PermissionDeniedError>()
+            .is_some()
+        {
+            XetErrorCode::PermissionDenied
+        } else if crate::hf_adapter::is_transient_download_error(&err) {
+            // A transient transfer failure (timeout, connection reset, 5xx/
+            // 408/429, stream truncation) that made it here exhausted every
+            // retry `download_http_with_resume` was willing to attempt.
+            XetErrorCode::NetworkError
+        } else {
+            XetErrorCode::Unknown
+        };
         let message = format!("{}", err);
         let details = format!("{:?}", err);
-        Self::new(XetErrorCode::Unknown, message, Some(details))
+        Self::new(code, message, Some(details))
     }
 }
 
@@ -4,7 +4,11 @@ use std::ptr;
 use std::sync::Arc;
 
 use crate::error::{XetError, XetErrorCode};
+use crate::http_config::HttpClientConfig;
+use crate::integrity::VerifyMode;
+use crate::network_policy::NetworkPolicy;
 use crate::progress::XetProgressCallback;
+use crate::retry::RetryConfig;
 use crate::{block_on, DownloadOptions, OperationContext, SnapshotOptions, XetClient};
 
 #[repr(C)]
@@ -14,6 +18,162 @@ pub struct XetConfig {
     pub cache_dir: *const c_char,
     pub max_concurrent_downloads: u32,
     pub enable_dedup: bool,
+    /// Optional transport tuning (proxy, custom CA roots, timeouts, extra
+    /// headers). May be null to use the default `reqwest` behavior.
+    pub http_config: *const XetHttpConfig,
+    /// Optional host allow/deny list (e.g. `*.hf.co`) constraining which
+    /// network destinations the adapter may contact. May be null to allow
+    /// every host not otherwise denied.
+    pub network_policy: *const XetNetworkPolicyConfig,
+    /// How strictly downloaded/cached content is checked against its
+    /// expected hash: `0` = off, `1` = size-only (the default), `2` = full
+    /// digest verification. Any other value falls back to size-only.
+    pub verify_mode: u8,
+    /// Maximum attempts for a whole-file-transfer retry (see `retry.rs`).
+    /// `0` falls back to the default of 4.
+    pub retry_max_attempts: u32,
+    /// Base delay in milliseconds for transfer-retry exponential backoff.
+    /// `0` falls back to the default of 250ms.
+    pub retry_base_delay_ms: u32,
+    /// Cap in milliseconds for transfer-retry exponential backoff. `0` falls
+    /// back to the default of 8000ms.
+    pub retry_max_delay_ms: u32,
+    /// Force the download write path back to plain blocking file I/O even
+    /// when the `io_uring` feature is compiled in and the kernel supports
+    /// it. See `io_writer.rs`.
+    pub force_disable_io_uring: bool,
+}
+
+/// Host allowlist/denylist for the adapter's outbound requests.
+#[repr(C)]
+pub struct XetNetworkPolicyConfig {
+    pub allow_hosts: *const *const c_char,
+    pub allow_host_count: usize,
+    pub deny_hosts: *const *const c_char,
+    pub deny_host_count: usize,
+}
+
+/// Convert the raw `verify_mode` byte from `XetConfig` into a `VerifyMode`.
+fn verify_mode_from_ffi(verify_mode: u8) -> VerifyMode {
+    match verify_mode {
+        0 => VerifyMode::Off,
+        2 => VerifyMode::Full,
+        _ => VerifyMode::SizeOnly,
+    }
+}
+
+/// Convert `XetConfig`'s `retry_*` fields into a `RetryConfig`, falling back
+/// to the default for any field left at `0`.
+fn retry_config_from_ffi(config: &XetConfig) -> RetryConfig {
+    let default = RetryConfig::default();
+    RetryConfig {
+        max_attempts: if config.retry_max_attempts > 0 {
+            config.retry_max_attempts
+        } else {
+            default.max_attempts
+        },
+        base_delay: if config.retry_base_delay_ms > 0 {
+            std::time::Duration::from_millis(config.retry_base_delay_ms as u64)
+        } else {
+            default.base_delay
+        },
+        max_delay: if config.retry_max_delay_ms > 0 {
+            std::time::Duration::from_millis(config.retry_max_delay_ms as u64)
+        } else {
+            default.max_delay
+        },
+    }
+}
+
+/// Convert a `XetNetworkPolicyConfig` C struct into a Rust-side `NetworkPolicy`.
+///
+/// # Safety
+///
+/// `config` must be either null or a valid pointer to a fully-initialized
+/// `XetNetworkPolicyConfig`, and both array pointers must be valid for
+/// `*_count` elements of valid, NUL-terminated C strings.
+unsafe fn network_policy_from_ffi(config: *const XetNetworkPolicyConfig) -> Option<NetworkPolicy> {
+    if config.is_null() {
+        return None;
+    }
+
+    let config = unsafe { &*config };
+
+    let allow: Vec<String> = unsafe {
+        (0..config.allow_host_count)
+            .filter_map(|i| c_str_to_string(*config.allow_hosts.add(i)))
+            .collect()
+    };
+    let deny: Vec<String> = unsafe {
+        (0..config.deny_host_count)
+            .filter_map(|i| c_str_to_string(*config.deny_hosts.add(i)))
+            .collect()
+    };
+
+    Some(NetworkPolicy::new(allow, deny))
+}
+
+/// Transport-level settings for the HTTP clients the adapter builds.
+#[repr(C)]
+pub struct XetHttpConfig {
+    pub proxy_url: *const c_char,
+    pub proxy_username: *const c_char,
+    pub proxy_password: *const c_char,
+    pub ca_cert_paths: *const *const c_char,
+    pub ca_cert_count: usize,
+    pub danger_accept_invalid_certs: bool,
+    /// 0 means "use the `reqwest` default".
+    pub connect_timeout_ms: u32,
+    /// 0 means "no overall request timeout".
+    pub request_timeout_ms: u32,
+    pub extra_header_keys: *const *const c_char,
+    pub extra_header_values: *const *const c_char,
+    pub extra_header_count: usize,
+}
+
+/// Convert a `XetHttpConfig` C struct into the Rust-side `HttpClientConfig`.
+///
+/// # Safety
+///
+/// `config` must be either null or a valid pointer to a fully-initialized
+/// `XetHttpConfig`, and all contained array pointers must be valid for
+/// `*_count` elements of valid, NUL-terminated C strings.
+unsafe fn http_client_config_from_ffi(config: *const XetHttpConfig) -> HttpClientConfig {
+    if config.is_null() {
+        return HttpClientConfig::default();
+    }
+
+    let config = unsafe { &*config };
+
+    let ca_cert_paths = unsafe {
+        (0..config.ca_cert_count)
+            .filter_map(|i| c_str_to_string(*config.ca_cert_paths.add(i)))
+            .map(std::path::PathBuf::from)
+            .collect()
+    };
+
+    let default_headers = unsafe {
+        (0..config.extra_header_count)
+            .filter_map(|i| {
+                let key = c_str_to_string(*config.extra_header_keys.add(i))?;
+                let value = c_str_to_string(*config.extra_header_values.add(i))?;
+                Some((key, value))
+            })
+            .collect()
+    };
+
+    HttpClientConfig {
+        proxy_url: unsafe { c_str_to_string(config.proxy_url) },
+        proxy_username: unsafe { c_str_to_string(config.proxy_username) },
+        proxy_password: unsafe { c_str_to_string(config.proxy_password) },
+        extra_root_cert_paths: ca_cert_paths,
+        danger_accept_invalid_certs: config.danger_accept_invalid_certs,
+        connect_timeout: (config.connect_timeout_ms > 0)
+            .then(|| std::time::Duration::from_millis(config.connect_timeout_ms as u64)),
+        request_timeout: (config.request_timeout_ms > 0)
+            .then(|| std::time::Duration::from_millis(config.request_timeout_ms as u64)),
+        default_headers,
+    }
 }
 
 #[repr(C)]
@@ -23,6 +183,11 @@ pub struct XetDownloadRequest {
     pub revision: *const c_char,
     pub filename: *const c_char,
     pub local_dir: *const c_char,
+    /// Bypass the local freshness window and force a conditional
+    /// revalidation request even for an entry that isn't stale yet.
+    pub force_revalidate: bool,
+    /// See [`crate::DownloadOptions::resume`].
+    pub resume: bool,
 }
 
 #[repr(C)]
@@ -69,6 +234,35 @@ unsafe fn c_str_to_string(s: *const c_char) -> Option<String> {
     }
 }
 
+/// A borrowed array of C strings, for FFI parameters that take a variable
+/// number of patterns/paths (e.g. `xet_download_snapshot`'s pattern lists)
+/// rather than a fixed config struct field.
+#[repr(C)]
+pub struct XetStringList {
+    pub items: *const *const c_char,
+    pub count: usize,
+}
+
+/// Convert a `XetStringList` into the `Option<Vec<String>>` shape
+/// `SnapshotOptions` expects: an empty list means "no filtering", matching
+/// how `None` is treated there.
+///
+/// # Safety
+///
+/// `list.items` must be valid for `list.count` elements of valid,
+/// NUL-terminated C strings, unless `list.count` is `0`.
+unsafe fn string_list_to_patterns(list: XetStringList) -> Option<Vec<String>> {
+    if list.count == 0 {
+        return None;
+    }
+
+    Some(
+        (0..list.count)
+            .filter_map(|i| c_str_to_string(*list.items.add(i)))
+            .collect(),
+    )
+}
+
 /// Create a new XET client.
 ///
 /// # Safety
@@ -94,12 +288,23 @@ pub unsafe extern "C" fn xet_client_new(config: *const XetConfig) -> *mut XetCli
             4
         };
 
-        match XetClient::new(
+        let http_config = http_client_config_from_ffi(config.http_config);
+        let network_policy = network_policy_from_ffi(config.network_policy).map(Arc::new);
+        let verify_mode = verify_mode_from_ffi(config.verify_mode);
+        let retry_config = retry_config_from_ffi(config);
+
+        match XetClient::new_advanced(
             endpoint,
             token,
             cache_dir,
             max_concurrent,
             config.enable_dedup,
+            None,
+            http_config,
+            network_policy,
+            verify_mode,
+            retry_config,
+            config.force_disable_io_uring,
         ) {
             Ok(client) => Box::into_raw(Box::new(client)),
             Err(_) => ptr::null_mut(),
@@ -275,6 +480,8 @@ pub unsafe extern "C" fn xet_download_file(
         repo_type: repo_type.as_deref(),
         revision: revision.as_deref(),
         local_dir: local_dir.as_deref(),
+        force_revalidate: request_ref.force_revalidate,
+        resume: request_ref.resume,
     };
     let context = OperationContext::new(cancel_check, progress);
 
@@ -295,7 +502,12 @@ pub unsafe extern "C" fn xet_download_file(
     }
 }
 
-/// Download all files from a repository.
+/// Download all files from a repository, optionally restricted to a subset
+/// via `allow_patterns`/`ignore_patterns` (each a `XetStringList` of shell-
+/// style globs, e.g. `*.safetensors`; a `!`-prefixed entry in either list
+/// always excludes, e.g. `!*.bin` — see
+/// [`crate::model_downloader::filter_patterns`]). An empty list means "no
+/// filtering", matching `SnapshotOptions`'s own `None` default.
 ///
 /// # Safety
 ///
@@ -303,6 +515,8 @@ pub unsafe extern "C" fn xet_download_file(
 /// - All pointers are valid or null
 /// - Strings are valid UTF-8
 /// - `out_path` must be freed with `xet_free_string`
+/// - `allow_patterns.items`/`ignore_patterns.items` are valid for their
+///   respective `count` elements of valid, NUL-terminated C strings
 #[no_mangle]
 pub unsafe extern "C" fn xet_download_snapshot(
     client: *mut XetClient,
@@ -310,6 +524,10 @@ pub unsafe extern "C" fn xet_download_snapshot(
     repo_type: *const c_char,
     revision: *const c_char,
     local_dir: *const c_char,
+    allow_patterns: XetStringList,
+    ignore_patterns: XetStringList,
+    force_revalidate: bool,
+    resume: bool,
     cancel_token: *const XetCancellationToken,
     out_path: *mut *mut c_char,
 ) -> *mut XetError {
@@ -346,14 +564,19 @@ pub unsafe extern "C" fn xet_download_snapshot(
         }
     };
 
+    let allow_patterns = unsafe { string_list_to_patterns(allow_patterns) };
+    let ignore_patterns = unsafe { string_list_to_patterns(ignore_patterns) };
+
     let cancel_check = unsafe { make_cancel_check(cancel_token) };
     let progress = client_ref.new_progress_operation();
     let options = SnapshotOptions {
         repo_type: repo_type.as_deref(),
         revision: revision.as_deref(),
         local_dir: &local_dir,
-        allow_patterns: None,
-        ignore_patterns: None,
+        allow_patterns,
+        ignore_patterns,
+        force_revalidate,
+        resume,
     };
     let context = OperationContext::new(cancel_check, progress);
 
@@ -374,6 +597,312 @@ pub unsafe extern "C" fn xet_download_snapshot(
     }
 }
 
+#[repr(C)]
+pub struct XetUploadRequest {
+    pub repo_id: *const c_char,
+    pub repo_type: *const c_char,
+    pub revision: *const c_char,
+    pub local_path: *const c_char,
+    pub remote_path: *const c_char,
+}
+
+/// Upload a single local file to a repository, content-defined-chunked so
+/// only the bytes the remote doesn't already have get transferred (see
+/// `chunker.rs`).
+///
+/// # Safety
+///
+/// Caller must ensure that:
+/// - All pointers are valid or null
+/// - Strings are valid UTF-8
+/// - `out_commit_oid` must be freed with `xet_free_string`
+#[no_mangle]
+pub unsafe extern "C" fn xet_upload_file(
+    client: *mut XetClient,
+    request: *const XetUploadRequest,
+    cancel_token: *const XetCancellationToken,
+    out_commit_oid: *mut *mut c_char,
+) -> *mut XetError {
+    if client.is_null() || request.is_null() || out_commit_oid.is_null() {
+        return XetError::new(
+            XetErrorCode::InvalidConfig,
+            "Invalid parameters".to_string(),
+            None,
+        );
+    }
+
+    let client_ref = unsafe { &*client };
+    let request_ref = unsafe { &*request };
+
+    let repo_id = match unsafe { c_str_to_string(request_ref.repo_id) } {
+        Some(s) => s,
+        None => {
+            return XetError::new(
+                XetErrorCode::InvalidConfig,
+                "Invalid repo_id".to_string(),
+                None,
+            );
+        }
+    };
+
+    let local_path = match unsafe { c_str_to_string(request_ref.local_path) } {
+        Some(s) => s,
+        None => {
+            return XetError::new(
+                XetErrorCode::InvalidConfig,
+                "Invalid local_path".to_string(),
+                None,
+            );
+        }
+    };
+
+    let remote_path = match unsafe { c_str_to_string(request_ref.remote_path) } {
+        Some(s) => s,
+        None => {
+            return XetError::new(
+                XetErrorCode::InvalidConfig,
+                "Invalid remote_path".to_string(),
+                None,
+            );
+        }
+    };
+
+    let repo_type = unsafe { c_str_to_string(request_ref.repo_type) };
+    let revision = unsafe { c_str_to_string(request_ref.revision) };
+
+    let cancel_check = unsafe { make_cancel_check(cancel_token) };
+    let progress = client_ref.new_progress_operation();
+    let context = OperationContext::new(cancel_check, progress);
+
+    let result = block_on(async {
+        client_ref
+            .upload_file_with_options(
+                &repo_id,
+                repo_type.as_deref(),
+                revision.as_deref(),
+                &local_path,
+                &remote_path,
+                context,
+            )
+            .await
+    });
+
+    match result {
+        Ok(commit_oid) => {
+            unsafe {
+                *out_commit_oid = CString::new(commit_oid).unwrap().into_raw();
+            }
+            ptr::null_mut()
+        }
+        Err(e) => XetError::from_anyhow(e),
+    }
+}
+
+/// Upload every file under `local_dir` to a repository in a single commit,
+/// each file content-defined-chunked the same way as `xet_upload_file`.
+///
+/// # Safety
+///
+/// Caller must ensure that:
+/// - All pointers are valid or null
+/// - Strings are valid UTF-8
+/// - `out_commit_oid` must be freed with `xet_free_string`
+#[no_mangle]
+pub unsafe extern "C" fn xet_upload_snapshot(
+    client: *mut XetClient,
+    repo_id: *const c_char,
+    repo_type: *const c_char,
+    revision: *const c_char,
+    local_dir: *const c_char,
+    cancel_token: *const XetCancellationToken,
+    out_commit_oid: *mut *mut c_char,
+) -> *mut XetError {
+    if client.is_null() || repo_id.is_null() || local_dir.is_null() || out_commit_oid.is_null() {
+        return XetError::new(
+            XetErrorCode::InvalidConfig,
+            "Invalid parameters".to_string(),
+            None,
+        );
+    }
+
+    let client_ref = unsafe { &*client };
+    let repo_id = match unsafe { c_str_to_string(repo_id) } {
+        Some(s) => s,
+        None => {
+            return XetError::new(
+                XetErrorCode::InvalidConfig,
+                "Invalid repo_id".to_string(),
+                None,
+            );
+        }
+    };
+
+    let repo_type = unsafe { c_str_to_string(repo_type) };
+    let revision = unsafe { c_str_to_string(revision) };
+    let local_dir = match unsafe { c_str_to_string(local_dir) } {
+        Some(s) => s,
+        None => {
+            return XetError::new(
+                XetErrorCode::InvalidConfig,
+                "Invalid local_dir".to_string(),
+                None,
+            );
+        }
+    };
+
+    let cancel_check = unsafe { make_cancel_check(cancel_token) };
+    let progress = client_ref.new_progress_operation();
+    let context = OperationContext::new(cancel_check, progress);
+
+    let result = block_on(async {
+        client_ref
+            .upload_snapshot_with_options(
+                &repo_id,
+                repo_type.as_deref(),
+                revision.as_deref(),
+                &local_dir,
+                None,
+                None,
+                context,
+            )
+            .await
+    });
+
+    match result {
+        Ok(commit_oid) => {
+            unsafe {
+                *out_commit_oid = CString::new(commit_oid).unwrap().into_raw();
+            }
+            ptr::null_mut()
+        }
+        Err(e) => XetError::from_anyhow(e),
+    }
+}
+
+/// Request to mount a repository revision as a read-only filesystem; see
+/// `xet_mount`.
+#[cfg(feature = "fuse")]
+#[repr(C)]
+pub struct XetMountRequest {
+    pub repo_id: *const c_char,
+    pub repo_type: *const c_char,
+    pub revision: *const c_char,
+    pub mountpoint: *const c_char,
+    /// Where lazily-materialized file content is cached.
+    pub cache_dir: *const c_char,
+}
+
+/// Mount `request.repo_id`'s revision as a read-only filesystem at
+/// `request.mountpoint`: directory listing is served from one `list_files`
+/// call made up front, and each file's content is downloaded into
+/// `request.cache_dir` on first read. Pass `cancel_token` to unmount
+/// automatically once it reports cancellation, in addition to calling
+/// `xet_unmount`.
+///
+/// # Safety
+///
+/// Caller must ensure that:
+/// - All pointers are valid or null
+/// - Strings are valid UTF-8
+/// - `request.mountpoint` and `request.cache_dir` are existing, writable
+///   directories
+/// - `out_mount` must be freed with `xet_unmount`
+#[cfg(feature = "fuse")]
+#[no_mangle]
+pub unsafe extern "C" fn xet_mount(
+    client: *mut XetClient,
+    request: *const XetMountRequest,
+    cancel_token: *const XetCancellationToken,
+    out_mount: *mut *mut crate::RepoFuseMount,
+) -> *mut XetError {
+    if client.is_null() || request.is_null() || out_mount.is_null() {
+        return XetError::new(
+            XetErrorCode::InvalidConfig,
+            "Invalid parameters".to_string(),
+            None,
+        );
+    }
+
+    let client_ref = unsafe { &*client };
+    let request_ref = unsafe { &*request };
+
+    let repo_id = match unsafe { c_str_to_string(request_ref.repo_id) } {
+        Some(s) => s,
+        None => {
+            return XetError::new(
+                XetErrorCode::InvalidConfig,
+                "Invalid repo_id".to_string(),
+                None,
+            );
+        }
+    };
+
+    let mountpoint = match unsafe { c_str_to_string(request_ref.mountpoint) } {
+        Some(s) => s,
+        None => {
+            return XetError::new(
+                XetErrorCode::InvalidConfig,
+                "Invalid mountpoint".to_string(),
+                None,
+            );
+        }
+    };
+
+    let cache_dir = match unsafe { c_str_to_string(request_ref.cache_dir) } {
+        Some(s) => s,
+        None => {
+            return XetError::new(
+                XetErrorCode::InvalidConfig,
+                "Invalid cache_dir".to_string(),
+                None,
+            );
+        }
+    };
+
+    let repo_type = unsafe { c_str_to_string(request_ref.repo_type) };
+    let revision = unsafe { c_str_to_string(request_ref.revision) };
+    let cancel_check = unsafe { make_cancel_check(cancel_token) };
+    let progress = client_ref.new_progress_operation();
+
+    let result = crate::RepoFuseMount::mount(
+        client_ref.clone(),
+        repo_id,
+        repo_type,
+        revision,
+        std::path::Path::new(&mountpoint),
+        std::path::Path::new(&cache_dir),
+        cancel_check,
+        progress,
+    );
+
+    match result {
+        Ok(mount) => {
+            unsafe {
+                *out_mount = Box::into_raw(Box::new(mount));
+            }
+            ptr::null_mut()
+        }
+        Err(e) => XetError::from_anyhow(e),
+    }
+}
+
+/// Unmount and free a mount handle returned by `xet_mount`.
+///
+/// # Safety
+///
+/// Caller must ensure that:
+/// - `mount` is either null or a valid pointer returned by `xet_mount`
+/// - `mount` is not used after calling this function
+#[cfg(feature = "fuse")]
+#[no_mangle]
+pub unsafe extern "C" fn xet_unmount(mount: *mut crate::RepoFuseMount) {
+    if !mount.is_null() {
+        unsafe {
+            let _ = Box::from_raw(mount);
+        }
+    }
+}
+
 /// Free a file list returned by `xet_list_files`.
 ///
 /// # Safety
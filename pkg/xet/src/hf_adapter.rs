@@ -1,13 +1,28 @@
+use crate::checkpoint;
+use crate::chunker;
+use crate::credentials::{base64_encode, host_of, CredentialProvider};
+use crate::hf_commit;
+use crate::http_config::HttpClientConfig;
+use crate::integrity::{self, ChecksumMismatchError, ContentVerifier, VerifyMode};
+use crate::io_writer::{DownloadWriter, IoWriterConfig};
+use crate::model_downloader::{self, AdaptiveConcurrencyConfig, ModelDownloader};
+use crate::network_policy::NetworkPolicy;
 use crate::progress::{OperationProgress, XetProgressPhase};
-use crate::xet_integration::{parse_xet_file_data_from_headers, XetFileData, XetTokenManager};
-use anyhow::{anyhow, Result};
-use futures::stream::{self, StreamExt};
+use crate::retry::{self, RetryConfig};
+use crate::revalidation;
+use crate::xet_integration::{
+    parse_xet_file_data_from_headers, XetFileData, XetTokenManager, XetTokenType,
+};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
 use tokio::time::sleep;
 use tracing::{debug, info};
 
@@ -16,10 +31,21 @@ pub struct HfAdapter {
     endpoint: String,
     token: Option<String>,
     cache_dir: Option<PathBuf>,
-    max_concurrent: usize,
+    /// Bounds for `download_snapshot`'s adaptive in-flight scheduler,
+    /// derived from the `max_concurrent` constructor argument.
+    concurrency: AdaptiveConcurrencyConfig,
     enable_dedup: bool,
     client: reqwest::Client,
+    no_redirect_client: reqwest::Client,
     xet_token_manager: Arc<tokio::sync::Mutex<XetTokenManager>>,
+    credential_provider: Option<Arc<dyn CredentialProvider>>,
+    network_policy: Option<Arc<NetworkPolicy>>,
+    verify_mode: VerifyMode,
+    /// Backoff bounds for whole-file-transfer retries (see `retry.rs`),
+    /// surfaced to callers via `XetConfig`'s `retry_*` fields.
+    retry_config: RetryConfig,
+    /// See `io_writer.rs`; surfaced via `XetConfig::force_disable_io_uring`.
+    io_writer_config: IoWriterConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -44,9 +70,20 @@ struct HfTreeItem {
 }
 
 const MAX_HTTP_RETRIES: usize = 3;
-const RETRY_BACKOFF_MS: u64 = 200;
+const RETRY_BACKOFF_BASE_MS: u64 = 200;
+const RETRY_BACKOFF_MAX_MS: u64 = 8_000;
 
 impl HfAdapter {
+    /// Send a request built by `builder`, retrying transient failures up to
+    /// `MAX_HTTP_RETRIES` times. A response rejected by `is_success` is
+    /// retried only if its status is transient (`408`, `429`, `5xx`) —
+    /// anything else (`401`, `403`, `404`, ...) is a permanent failure and
+    /// returns immediately rather than wasting round trips. Likewise, a
+    /// transport-level error is only retried if it looks transient
+    /// (timeout/connect/mid-request). The wait between retries honors a
+    /// `Retry-After` response header when present, falling back to
+    /// exponential backoff with full jitter so that many concurrent callers
+    /// (e.g. a `download_snapshot` fan-out) don't retry in lockstep.
     async fn send_with_retry<F, S>(
         &self,
         mut builder: F,
@@ -64,11 +101,20 @@ impl HfAdapter {
                         return Ok(resp);
                     }
 
+                    let status = resp.status();
+                    if !is_retryable_status(status) {
+                        return Err(anyhow!(
+                            "{} failed: HTTP {} (not retryable)",
+                            description,
+                            status
+                        ));
+                    }
+
                     debug!(
                         "[RETRY] {} attempt {} failed with HTTP {}",
                         description,
                         attempt + 1,
-                        resp.status()
+                        status
                     );
 
                     if attempt == MAX_HTTP_RETRIES {
@@ -76,11 +122,23 @@ impl HfAdapter {
                             "{} failed after {} attempts: HTTP {}",
                             description,
                             attempt + 1,
-                            resp.status()
+                            status
                         ));
                     }
+
+                    let retry_after = resp
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(parse_retry_after);
+
+                    sleep(retry_delay(attempt, retry_after)).await;
                 }
                 Err(err) => {
+                    if !is_transient_reqwest_error(&err) {
+                        return Err(anyhow!("{} failed: {} (not retryable)", description, err));
+                    }
+
                     debug!(
                         "[RETRY] {} attempt {} errored: {}",
                         description,
@@ -96,50 +154,86 @@ impl HfAdapter {
                             err
                         ));
                     }
+
+                    sleep(retry_delay(attempt, None)).await;
                 }
             }
-
-            sleep(Duration::from_millis(
-                RETRY_BACKOFF_MS * (attempt as u64 + 1),
-            ))
-            .await;
         }
 
         unreachable!("retry loop should always return or err");
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         endpoint: String,
         token: Option<String>,
         cache_dir: Option<String>,
         max_concurrent: usize,
         enable_dedup: bool,
+        credential_provider: Option<Box<dyn CredentialProvider>>,
+        http_config: HttpClientConfig,
+        network_policy: Option<Arc<NetworkPolicy>>,
+        verify_mode: VerifyMode,
+        retry_config: RetryConfig,
+        force_disable_io_uring: bool,
     ) -> Result<Self> {
         let cache_dir = cache_dir.map(PathBuf::from);
+        let credential_provider: Option<Arc<dyn CredentialProvider>> =
+            credential_provider.map(Arc::from);
+
+        if let Some(ref policy) = network_policy {
+            policy.check_url(&endpoint)?;
+        }
+
+        // A per-host credential, if the provider covers the endpoint host,
+        // takes precedence over the single fallback `token`.
+        let endpoint_auth = endpoint_auth_header(&credential_provider, &endpoint, token.as_deref());
 
         let mut headers = reqwest::header::HeaderMap::new();
-        if let Some(ref token) = token {
+        if let Some(ref auth) = endpoint_auth {
             headers.insert(
                 reqwest::header::AUTHORIZATION,
-                reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token))?,
+                reqwest::header::HeaderValue::from_str(auth)?,
             );
         }
 
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
+        let merged_headers = http_config.merged_headers(headers)?;
+
+        let client = http_config
+            .apply(reqwest::Client::builder().default_headers(merged_headers.clone()))?
+            .build()?;
+
+        let no_redirect_client = http_config
+            .apply(
+                reqwest::Client::builder()
+                    .redirect(reqwest::redirect::Policy::none())
+                    .default_headers(merged_headers),
+            )?
             .build()?;
 
-        let xet_token_manager =
-            Arc::new(tokio::sync::Mutex::new(XetTokenManager::new(token.clone())));
+        let xet_token_manager = Arc::new(tokio::sync::Mutex::new(XetTokenManager::new(
+            token.clone(),
+            credential_provider.clone(),
+            http_config.clone(),
+            network_policy.clone(),
+        )));
 
         Ok(HfAdapter {
             endpoint,
             token,
             cache_dir,
-            max_concurrent,
+            concurrency: AdaptiveConcurrencyConfig::from_max_concurrent(max_concurrent),
             enable_dedup,
             client,
+            no_redirect_client,
             xet_token_manager,
+            credential_provider,
+            network_policy,
+            verify_mode,
+            retry_config,
+            io_writer_config: IoWriterConfig {
+                force_disable_io_uring,
+            },
         })
     }
 
@@ -151,6 +245,10 @@ impl HfAdapter {
         let revision = revision.unwrap_or("main");
         let url = format!("{}/api/models/{}/tree/{}", self.endpoint, repo_id, revision);
 
+        if let Some(ref policy) = self.network_policy {
+            policy.check_url(&url)?;
+        }
+
         // Make HTTP request to HF API
         let response = self
             .send_with_retry(
@@ -188,6 +286,8 @@ impl HfAdapter {
         repo_type: Option<&str>,
         revision: Option<&str>,
         local_dir: Option<&str>,
+        force_revalidate: bool,
+        resume: bool,
         cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
         progress: Option<OperationProgress>,
     ) -> Result<String> {
@@ -219,6 +319,8 @@ impl HfAdapter {
                 revision,
                 local_dir,
                 &file_info,
+                force_revalidate,
+                resume,
                 cancel_check,
                 progress.as_ref().map(|p| p.clone_for_tasks()),
             )
@@ -240,97 +342,599 @@ impl HfAdapter {
         local_dir: &str,
         allow_patterns: Option<Vec<String>>,
         ignore_patterns: Option<Vec<String>>,
+        force_revalidate: bool,
+        resume: bool,
         cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
         progress: Option<OperationProgress>,
     ) -> Result<String> {
         let revision = revision.unwrap_or("main");
+        let files = self.list_files(repo_id, Some(revision)).await?;
+
+        let adapter = self.clone();
+        let repo_id = repo_id.to_string();
+        let repo_type = repo_type.map(|s| s.to_string());
+        let revision = revision.to_string();
+
+        model_downloader::run_snapshot_download(
+            files,
+            local_dir,
+            &allow_patterns,
+            &ignore_patterns,
+            self.concurrency,
+            cancel_check,
+            progress,
+            move |file, cancel_check, progress| {
+                let adapter = adapter.clone();
+                let repo_id = repo_id.clone();
+                let repo_type = repo_type.clone();
+                let revision = revision.clone();
+                let local_dir = local_dir.to_string();
+                async move {
+                    adapter
+                        .download_file_with_info(
+                            &repo_id,
+                            repo_type.as_deref(),
+                            &revision,
+                            Some(&local_dir),
+                            &file,
+                            force_revalidate,
+                            resume,
+                            cancel_check,
+                            progress,
+                        )
+                        .await
+                }
+            },
+        )
+        .await
+    }
+
+    /// Upload a single local file to `repo_id`, committing it in its own
+    /// HF "commit" API call. Mirrors `download_file_with_cancel`'s shape;
+    /// see [`Self::upload_snapshot`] for batching many files into one commit.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn upload_file(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_path: &str,
+        remote_path: &str,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        let repo_type = repo_type.unwrap_or("model");
+        let revision = revision.unwrap_or("main");
+
         if let Some(ref tracker) = progress {
             tracker.set_phase(XetProgressPhase::Scanning, true);
         }
 
-        // List all files in the repository
-        let files = self.list_files(repo_id, Some(revision)).await?;
+        let local = Path::new(local_path);
+        let size = fs::metadata(local)
+            .await
+            .with_context(|| format!("failed to stat {local_path} for upload"))?
+            .len();
+        if let Some(ref tracker) = progress {
+            tracker.set_total_hint(1, size);
+            tracker.set_phase(XetProgressPhase::Downloading, true);
+        }
+
+        let commit_file = self
+            .upload_one_file(
+                repo_id,
+                repo_type,
+                revision,
+                local,
+                remote_path,
+                &cancel_check,
+                &progress,
+            )
+            .await?;
+
+        let commit_oid = match commit_file {
+            Some(file) => {
+                let summary = format!("Upload {}", remote_path);
+                self.commit(repo_id, repo_type, revision, &summary, std::slice::from_ref(&file))
+                    .await?
+            }
+            None => String::new(),
+        };
+
+        if let Some(tracker) = progress {
+            tracker.finalize();
+        }
+
+        Ok(commit_oid)
+    }
+
+    /// Upload every file under `local_dir` (after `allow_patterns`/
+    /// `ignore_patterns` filtering) to `repo_id` in a single commit.
+    /// Mirrors `download_snapshot`'s shape, but uploads one file at a time
+    /// rather than fanning out — the whole point is a single atomic commit
+    /// across every resolved file, so there is no benefit to a concurrent
+    /// in-flight pool the way there is for independent downloads.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn upload_snapshot(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: &str,
+        allow_patterns: Option<Vec<String>>,
+        ignore_patterns: Option<Vec<String>>,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        let repo_type = repo_type.unwrap_or("model");
+        let revision = revision.unwrap_or("main");
+
+        if let Some(ref tracker) = progress {
+            tracker.set_phase(XetProgressPhase::Scanning, true);
+        }
 
-        // Apply pattern filtering
-        let filtered_files: Vec<_> = files
+        let local_root = Path::new(local_dir);
+        let local_files = collect_local_files(local_root).await?;
+        let candidates: Vec<HfFileInfo> = local_files
             .into_iter()
-            .filter(|f| {
-                if let Some(ref allow) = allow_patterns {
-                    if !allow.iter().any(|p| f.path.contains(p)) {
-                        return false;
-                    }
-                }
-                if let Some(ref ignore) = ignore_patterns {
-                    if ignore.iter().any(|p| f.path.contains(p)) {
-                        return false;
-                    }
-                }
-                true
+            .map(|(path, size)| HfFileInfo {
+                path,
+                hash: String::new(),
+                size,
+                xet_hash: None,
             })
             .collect();
 
-        let total_bytes: u64 = filtered_files.iter().map(|f| f.size).sum();
+        let filtered = model_downloader::filter_patterns(candidates, &allow_patterns, &ignore_patterns);
+
+        let total_bytes: u64 = filtered.iter().map(|f| f.size).sum();
         if let Some(ref tracker) = progress {
-            tracker.set_total_hint(filtered_files.len(), total_bytes);
+            tracker.set_total_hint(filtered.len(), total_bytes);
             tracker.set_phase(XetProgressPhase::Downloading, true);
         }
 
-        // Create local directory if needed
-        fs::create_dir_all(local_dir).await?;
+        let mut commit_files = Vec::with_capacity(filtered.len());
+        for file in &filtered {
+            if is_cancelled(&cancel_check) {
+                return Err(anyhow!("Upload cancelled"));
+            }
 
-        // Download files in parallel with controlled concurrency
-        let max_concurrent = self.max_concurrent.max(1).min(filtered_files.len().max(1));
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(max_concurrent));
-        let cancel_check = cancel_check.map(|c| c as Arc<_>);
-        let progress_shared = progress.as_ref().map(|p| p.clone_for_tasks());
+            let local_path = local_root.join(&file.path);
+            if let Some(commit_file) = self
+                .upload_one_file(
+                    repo_id,
+                    repo_type,
+                    revision,
+                    &local_path,
+                    &file.path,
+                    &cancel_check,
+                    &progress,
+                )
+                .await?
+            {
+                commit_files.push(commit_file);
+            }
+        }
 
-        let download_futures = filtered_files.into_iter().map(|file| {
-            let semaphore = semaphore.clone();
-            let adapter = self.clone();
-            let repo_id = repo_id.to_string();
-            let repo_type = repo_type.map(|s| s.to_string());
-            let revision = revision.to_string();
-            let local_dir = local_dir.to_string();
-            let cancel_check = cancel_check.clone();
-            let progress = progress_shared.clone();
+        let commit_oid = if commit_files.is_empty() {
+            String::new()
+        } else {
+            let summary = format!("Upload {} files", commit_files.len());
+            self.commit(repo_id, repo_type, revision, &summary, &commit_files)
+                .await?
+        };
 
-            async move {
-                let _permit = semaphore.acquire().await?;
+        if let Some(tracker) = progress {
+            tracker.finalize();
+        }
 
-                if is_cancelled(&cancel_check) {
-                    return Err(anyhow!("Download cancelled"));
-                }
+        Ok(commit_oid)
+    }
 
-                adapter
-                    .download_file_with_info(
-                        &repo_id,
-                        repo_type.as_deref(),
-                        &revision,
-                        Some(&local_dir),
-                        &file,
-                        cancel_check.clone(),
-                        progress.as_ref().map(|p| p.clone_for_tasks()),
-                    )
-                    .await
+    /// Read, hash, and preupload-negotiate `local_path`, then upload its
+    /// content via whichever path the negotiation selected. Returns `None`
+    /// when the server says this file should be excluded from the commit
+    /// entirely (`shouldIgnore`), `Some` otherwise.
+    #[allow(clippy::too_many_arguments)]
+    async fn upload_one_file(
+        &self,
+        repo_id: &str,
+        repo_type: &str,
+        revision: &str,
+        local_path: &Path,
+        remote_path: &str,
+        cancel_check: &Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: &Option<OperationProgress>,
+    ) -> Result<Option<hf_commit::CommitFile>> {
+        if is_cancelled(cancel_check) {
+            return Err(anyhow!("Upload cancelled"));
+        }
+
+        let data = fs::read(local_path)
+            .await
+            .with_context(|| format!("failed to read {local_path:?} for upload"))?;
+        let size = data.len() as u64;
+        let sha256_hex = hf_commit::sha256_hex(&data);
+        let sample_len = data.len().min(512);
+        let sample_base64 = base64_encode(&data[..sample_len]);
+
+        if let Some(tracker) = progress {
+            tracker.ensure_file_entry(remote_path, size);
+        }
+
+        let pending = hf_commit::PendingUpload {
+            path: remote_path,
+            size,
+            sha256_hex: &sha256_hex,
+            sample_base64: &sample_base64,
+        };
+
+        let mut decisions = self
+            .preupload(repo_id, repo_type, revision, std::slice::from_ref(&pending))
+            .await?;
+        let decision = decisions
+            .pop()
+            .ok_or_else(|| anyhow!("preupload returned no decision for {remote_path}"))?;
+
+        if decision.should_ignore {
+            debug!("[UPLOAD] {remote_path} is ignored by the repo, skipping");
+            if let Some(tracker) = progress {
+                tracker.update_file_absolute(remote_path, size, size, true);
             }
-        });
+            return Ok(None);
+        }
+
+        if !decision.is_lfs() {
+            let content_base64 = base64_encode(&data);
+            if let Some(tracker) = progress {
+                tracker.update_file_absolute(remote_path, size, size, true);
+            }
+            return Ok(Some(hf_commit::CommitFile::Inline {
+                path: remote_path.to_string(),
+                content_base64,
+            }));
+        }
 
-        // Execute all downloads and collect results
-        let results: Vec<Result<String>> = stream::iter(download_futures)
-            .buffer_unordered(max_concurrent)
-            .collect()
-            .await;
+        if is_cancelled(cancel_check) {
+            return Err(anyhow!("Upload cancelled"));
+        }
 
-        // Check for errors
-        for result in results {
-            result?;
+        // LFS-eligible: prefer the XET content-defined-chunking path (only
+        // chunks the remote doesn't already have get transferred) and fall
+        // back to a plain LFS PUT if XET isn't available for this repo.
+        if self.enable_dedup {
+            match self
+                .upload_via_xet(
+                    repo_id,
+                    repo_type,
+                    revision,
+                    local_path,
+                    remote_path,
+                    progress.as_ref().map(|p| p.clone_for_tasks()),
+                )
+                .await
+            {
+                Ok(oid) => {
+                    return Ok(Some(hf_commit::CommitFile::Lfs {
+                        path: remote_path.to_string(),
+                        oid,
+                        size,
+                    }));
+                }
+                Err(err) => {
+                    debug!("[XET] upload falling back to plain LFS for {remote_path}: {err:?}");
+                }
+            }
         }
 
+        self.upload_via_lfs_batch(repo_id, repo_type, remote_path, &data, &sha256_hex, size, progress)
+            .await?;
+
+        Ok(Some(hf_commit::CommitFile::Lfs {
+            path: remote_path.to_string(),
+            oid: sha256_hex,
+            size,
+        }))
+    }
+
+    /// Negotiate upload mode for `files` via `POST .../preupload/{revision}`:
+    /// whether each should be committed inline (`"regular"`) or through
+    /// LFS/XET (`"lfs"`), or skipped entirely (`shouldIgnore`).
+    async fn preupload(
+        &self,
+        repo_id: &str,
+        repo_type: &str,
+        revision: &str,
+        files: &[hf_commit::PendingUpload<'_>],
+    ) -> Result<Vec<hf_commit::PreuploadFileResponse>> {
+        let url = format!(
+            "{}/api/{}s/{}/preupload/{}",
+            self.endpoint, repo_type, repo_id, revision
+        );
+
+        if let Some(ref policy) = self.network_policy {
+            policy.check_url(&url)?;
+        }
+
+        let body = hf_commit::preupload_request_body(files)?;
+        let auth_header = auth_header_for_url(&self.credential_provider, &url, self.token.as_deref());
+
+        let response = self
+            .send_with_retry(
+                || {
+                    let mut builder = self
+                        .client
+                        .post(&url)
+                        .header(reqwest::header::CONTENT_TYPE, "application/json")
+                        .body(body.clone());
+                    if let Some(ref auth) = auth_header {
+                        builder = builder.header(reqwest::header::AUTHORIZATION, auth.clone());
+                    }
+                    builder
+                },
+                "preupload",
+                |resp| resp.status().is_success(),
+            )
+            .await?;
+
+        let parsed: hf_commit::PreuploadResponse =
+            response.json().await.context("invalid preupload response")?;
+        Ok(parsed.files)
+    }
+
+    /// Commit `files` to `repo_id`'s `revision` via `POST .../commit/{revision}`,
+    /// returning the resulting commit oid.
+    async fn commit(
+        &self,
+        repo_id: &str,
+        repo_type: &str,
+        revision: &str,
+        summary: &str,
+        files: &[hf_commit::CommitFile],
+    ) -> Result<String> {
+        let url = format!(
+            "{}/api/{}s/{}/commit/{}",
+            self.endpoint, repo_type, repo_id, revision
+        );
+
+        if let Some(ref policy) = self.network_policy {
+            policy.check_url(&url)?;
+        }
+
+        let body = hf_commit::build_commit_ndjson(summary, files)?;
+        let auth_header = auth_header_for_url(&self.credential_provider, &url, self.token.as_deref());
+
+        let response = self
+            .send_with_retry(
+                || {
+                    let mut builder = self
+                        .client
+                        .post(&url)
+                        .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+                        .body(body.clone());
+                    if let Some(ref auth) = auth_header {
+                        builder = builder.header(reqwest::header::AUTHORIZATION, auth.clone());
+                    }
+                    builder
+                },
+                "commit",
+                |resp| resp.status().is_success(),
+            )
+            .await?;
+
+        let parsed: hf_commit::CommitResponse =
+            response.json().await.context("invalid commit response")?;
+        Ok(parsed.commit_oid.unwrap_or_default())
+    }
+
+    /// Upload `local_path`'s content through xet-core's content-defined
+    /// chunking and dedup path, reusing the same `XetTokenManager` the
+    /// download side refreshes against (keyed on the write-token route so a
+    /// mid-upload token refresh re-fetches from the same place). Returns the
+    /// resulting CAS hash to register as the commit's `lfsFile` oid.
+    async fn upload_via_xet(
+        &self,
+        repo_id: &str,
+        repo_type: &str,
+        revision: &str,
+        local_path: &Path,
+        remote_path: &str,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        use crate::xet_uploader::XetUploader;
+
+        let write_token_url = format!(
+            "{}/api/{}s/{}/xet-write-token/{}",
+            self.endpoint, repo_type, repo_id, revision
+        );
+
+        let mut token_manager = self.xet_token_manager.lock().await;
+        let connection_info = token_manager
+            .fetch_xet_connection_info_from_repo(
+                XetTokenType::Write,
+                repo_id,
+                repo_type,
+                Some(revision),
+                &self.endpoint,
+            )
+            .await?;
+        drop(token_manager);
+
+        info!("[XET] uploading {remote_path} via write endpoint: {}", connection_info.endpoint);
+
+        // `file_hash` isn't meaningful for an upload (there's no content hash
+        // to look up yet); only `refresh_route` is read by a later token
+        // refresh, and it must point back at this same write-token route.
+        let file_data = XetFileData {
+            file_hash: String::new(),
+            refresh_route: write_token_url,
+        };
+
+        let uploader =
+            XetUploader::new(&connection_info, &file_data, self.xet_token_manager.clone()).await?;
+        let hash = uploader.upload_file(local_path, remote_path, progress).await?;
+
+        Ok(hash.hex())
+    }
+
+    /// Upload `data` (already read into memory, with precomputed whole-file
+    /// `oid`/`size`) via the plain Git LFS batch API, content-defined-chunked
+    /// so the remote only needs to receive the bytes it's actually missing:
+    /// split `data` with [`chunker::chunk_stream`], batch every chunk's
+    /// blake3 hash to the LFS batch endpoint in one request to learn which
+    /// already exist, [`chunker::merge_known_chunks`] the result into runs,
+    /// and PUT only the chunks the remote didn't already have. The file is
+    /// still registered in the commit under its whole-file `oid` — chunking
+    /// only changes what gets transferred, not what gets committed.
+    async fn upload_via_lfs_batch(
+        &self,
+        repo_id: &str,
+        repo_type: &str,
+        remote_path: &str,
+        data: &[u8],
+        oid: &str,
+        size: u64,
+        progress: &Option<OperationProgress>,
+    ) -> Result<()> {
         if let Some(tracker) = progress {
-            tracker.finalize();
+            tracker.set_phase(XetProgressPhase::Hashing, true);
+        }
+
+        let chunks = chunker::chunk_stream(data, &chunker::ChunkerConfig::default());
+        if chunks.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(tracker) = progress {
+            tracker.set_phase(XetProgressPhase::Downloading, true);
+        }
+
+        // HF's classic git remote omits the "s" suffix for models but keeps
+        // it ("datasets"/"spaces") for every other repo type.
+        let git_prefix = if repo_type == "model" {
+            String::new()
+        } else {
+            format!("{}s/", repo_type)
+        };
+        let batch_url = format!(
+            "{}/{}{}.git/info/lfs/objects/batch",
+            self.endpoint, git_prefix, repo_id
+        );
+
+        if let Some(ref policy) = self.network_policy {
+            policy.check_url(&batch_url)?;
+        }
+
+        let objects: Vec<(&str, u64)> = chunks
+            .iter()
+            .map(|c| (c.hash_hex.as_str(), c.len as u64))
+            .collect();
+        let body = hf_commit::lfs_batch_request_body(&objects)?;
+        let auth_header =
+            auth_header_for_url(&self.credential_provider, &batch_url, self.token.as_deref());
+
+        let response = self
+            .send_with_retry(
+                || {
+                    let mut builder = self
+                        .client
+                        .post(&batch_url)
+                        .header(reqwest::header::CONTENT_TYPE, "application/vnd.git-lfs+json")
+                        .header(reqwest::header::ACCEPT, "application/vnd.git-lfs+json")
+                        .body(body.clone());
+                    if let Some(ref auth) = auth_header {
+                        builder = builder.header(reqwest::header::AUTHORIZATION, auth.clone());
+                    }
+                    builder
+                },
+                "lfs batch upload",
+                |resp| resp.status().is_success(),
+            )
+            .await?;
+
+        let batch: hf_commit::LfsBatchResponse =
+            response.json().await.context("invalid LFS batch response")?;
+        let mut actions_by_oid: HashMap<String, Option<hf_commit::LfsAction>> = HashMap::new();
+        for object in batch.objects {
+            if let Some(err) = object.error {
+                return Err(anyhow!(
+                    "LFS batch upload rejected {}: {} ({})",
+                    object.oid,
+                    err.message,
+                    err.code
+                ));
+            }
+            actions_by_oid.insert(object.oid, object.actions.and_then(|a| a.upload));
         }
 
-        Ok(local_dir.to_string())
+        let known: Vec<bool> = chunks
+            .iter()
+            .map(|c| {
+                actions_by_oid
+                    .get(&c.hash_hex)
+                    .map(|action| action.is_none())
+                    .unwrap_or(false)
+            })
+            .collect();
+        let merged = chunker::merge_known_chunks(&chunks, &known);
+
+        let mut uploaded_bytes = 0u64;
+        for range in merged {
+            match range {
+                chunker::MergedRange::Known { len, .. } => {
+                    debug!("[LFS] skipping {len} already-present bytes of {remote_path} ({oid})");
+                    uploaded_bytes += len as u64;
+                    if let Some(tracker) = progress {
+                        tracker.update_file_absolute(remote_path, uploaded_bytes, size, false);
+                    }
+                }
+                chunker::MergedRange::Unknown { offset, len } => {
+                    for chunk in chunks
+                        .iter()
+                        .filter(|c| c.offset >= offset && c.offset < offset + len)
+                    {
+                        let action = actions_by_oid
+                            .get(&chunk.hash_hex)
+                            .and_then(|a| a.clone())
+                            .ok_or_else(|| anyhow!("LFS batch response missing object {}", chunk.hash_hex))?;
+
+                        if let Some(ref policy) = self.network_policy {
+                            policy.check_url(&action.href)?;
+                        }
+
+                        let chunk_body = Bytes::copy_from_slice(&data[chunk.offset..chunk.offset + chunk.len]);
+                        self.send_with_retry(
+                            || {
+                                let mut builder = self.client.put(&action.href).body(chunk_body.clone());
+                                for (key, value) in &action.header {
+                                    if let (Ok(name), Ok(value)) = (
+                                        reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+                                        reqwest::header::HeaderValue::from_str(value),
+                                    ) {
+                                        builder = builder.header(name, value);
+                                    }
+                                }
+                                builder
+                            },
+                            "lfs chunk PUT",
+                            |resp| resp.status().is_success(),
+                        )
+                        .await?;
+
+                        uploaded_bytes += chunk.len as u64;
+                        if let Some(tracker) = progress {
+                            tracker.update_file_absolute(remote_path, uploaded_bytes, size, false);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(tracker) = progress {
+            tracker.update_file_absolute(remote_path, size, size, true);
+        }
+
+        Ok(())
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -341,6 +945,8 @@ impl HfAdapter {
         revision: &str,
         local_dir: Option<&str>,
         file_info: &HfFileInfo,
+        force_revalidate: bool,
+        resume: bool,
         cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
         progress: Option<OperationProgress>,
     ) -> Result<String> {
@@ -360,21 +966,47 @@ impl HfAdapter {
             fs::create_dir_all(parent).await?;
         }
 
-        // Check cache hit
+        let cached_metadata = revalidation::load(&destination);
+
+        // Check cache hit: an exact size match that is also still within its
+        // freshness window can be returned without talking to the network at all.
         if destination.exists() {
             if let Ok(metadata) = fs::metadata(&destination).await {
                 if metadata.len() == file_info.size {
-                    debug!("[CACHE HIT] {} ({} bytes)", file_info.path, file_info.size);
-                    if let Some(ref tracker) = progress {
-                        tracker.ensure_file_entry(&file_info.path, file_info.size);
-                        tracker.update_file_absolute(
-                            &file_info.path,
-                            file_info.size,
-                            file_info.size,
-                            true,
-                        );
+                    let fresh = !force_revalidate
+                        && cached_metadata
+                            .as_ref()
+                            .map(revalidation::is_fresh)
+                            .unwrap_or(false);
+
+                    if fresh {
+                        match integrity::verify_cached_file(self.verify_mode, &destination, file_info)
+                            .await
+                        {
+                            Ok(()) => {
+                                debug!(
+                                    "[CACHE HIT] {} ({} bytes, fresh)",
+                                    file_info.path, file_info.size
+                                );
+                                if let Some(ref tracker) = progress {
+                                    tracker.ensure_file_entry(&file_info.path, file_info.size);
+                                    tracker.update_file_absolute(
+                                        &file_info.path,
+                                        file_info.size,
+                                        file_info.size,
+                                        true,
+                                    );
+                                }
+                                return Ok(destination.to_string_lossy().to_string());
+                            }
+                            Err(err) => {
+                                debug!(
+                                    "[CACHE MISS] {} - content verification failed ({err}), re-downloading",
+                                    file_info.path
+                                );
+                            }
+                        }
                     }
-                    return Ok(destination.to_string_lossy().to_string());
                 } else {
                     debug!(
                         "[CACHE MISS] {} - size mismatch (cached: {}, expected: {})",
@@ -392,31 +1024,89 @@ impl HfAdapter {
             self.endpoint, repo_id, revision, file_info.path
         );
 
-        // Make a HEAD request without following redirects to capture XET headers
-        let no_redirect_client = reqwest::Client::builder()
-            .redirect(reqwest::redirect::Policy::none())
-            .build()?;
+        if let Some(ref policy) = self.network_policy {
+            policy.check_url(&download_url)?;
+        }
 
-        let auth_header = self.token.as_ref().map(|t| format!("Bearer {}", t));
+        // Make a HEAD request without following redirects to capture XET headers.
+        // When we have a cached record for this destination, add conditional
+        // headers so a still-current file short-circuits via `304 Not Modified`.
+        let auth_header =
+            auth_header_for_url(&self.credential_provider, &download_url, self.token.as_deref());
+        let can_revalidate = destination.exists() && cached_metadata.is_some();
 
         let head_response = self
             .send_with_retry(
                 || {
-                    let mut builder = no_redirect_client.head(&download_url);
+                    let mut builder = self.no_redirect_client.head(&download_url);
                     if let Some(ref auth) = auth_header {
                         builder = builder.header(reqwest::header::AUTHORIZATION, auth.clone());
                     }
+                    if can_revalidate {
+                        if let Some(ref etag) = cached_metadata.as_ref().unwrap().etag {
+                            builder = builder
+                                .header(reqwest::header::IF_NONE_MATCH, etag.clone());
+                        }
+                        if let Some(ref last_modified) =
+                            cached_metadata.as_ref().unwrap().last_modified
+                        {
+                            builder = builder.header(
+                                reqwest::header::IF_MODIFIED_SINCE,
+                                last_modified.clone(),
+                            );
+                        }
+                    }
                     builder
                 },
                 "head request",
-                |resp| resp.status().is_success() || resp.status().is_redirection(),
+                |resp| {
+                    resp.status().is_success()
+                        || resp.status().is_redirection()
+                        || resp.status() == reqwest::StatusCode::NOT_MODIFIED
+                },
             )
             .await?;
 
+        if head_response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            let cached = cached_metadata.as_ref().unwrap();
+            let xet_hash_changed = parse_xet_file_data_from_headers(head_response.headers())
+                .map(|d| Some(d.file_hash) != cached.xet_hash)
+                .unwrap_or(false);
+
+            if !xet_hash_changed {
+                debug!(
+                    "[304 NOT MODIFIED] {} - skipping transfer",
+                    file_info.path
+                );
+                revalidation::save(
+                    &destination,
+                    &revalidation::metadata_from_headers(
+                        head_response.headers(),
+                        cached.xet_hash.as_deref(),
+                    ),
+                );
+                if let Some(ref tracker) = progress {
+                    tracker.ensure_file_entry(&file_info.path, file_info.size);
+                    tracker.update_file_absolute(
+                        &file_info.path,
+                        file_info.size,
+                        file_info.size,
+                        true,
+                    );
+                }
+                return Ok(destination.to_string_lossy().to_string());
+            }
+
+            debug!(
+                "[XET] cached hash for {} is stale, falling through to full download",
+                file_info.path
+            );
+        }
+
         let xet_file_data = parse_xet_file_data_from_headers(head_response.headers());
 
         // Try XET download if available and enabled
-        if let Some(xet_data) = xet_file_data {
+        if let Some(ref xet_data) = xet_file_data {
             if self.enable_dedup {
                 info!("[XET] File has XET support - hash: {}", xet_data.file_hash);
                 debug!("[XET] Refresh route: {}", xet_data.refresh_route);
@@ -424,7 +1114,7 @@ impl HfAdapter {
                 match self
                     .download_with_xet(
                         &file_info.path,
-                        &xet_data,
+                        xet_data,
                         &destination,
                         file_info.size,
                         cancel_check.clone(),
@@ -432,7 +1122,16 @@ impl HfAdapter {
                     )
                     .await
                 {
-                    Ok(path) => return Ok(path),
+                    Ok(path) => {
+                        revalidation::save(
+                            &destination,
+                            &revalidation::metadata_from_headers(
+                                head_response.headers(),
+                                Some(xet_data.file_hash.as_str()),
+                            ),
+                        );
+                        return Ok(path);
+                    }
                     Err(err) => {
                         debug!("[XET] Falling back to HTTP download: {err:?}");
                     }
@@ -448,43 +1147,33 @@ impl HfAdapter {
             return Err(anyhow!("Download cancelled"));
         }
 
-        // Regular HTTP download (fallback or primary)
-        let response = self
-            .send_with_retry(
-                || self.client.get(&download_url),
-                "download request",
-                |resp| resp.status().is_success(),
-            )
-            .await?;
-
-        let expected_total = response.content_length().unwrap_or(file_info.size);
-        if let Some(ref tracker) = progress {
-            tracker.ensure_file_entry(&file_info.path, expected_total);
-        }
-
-        let mut stream = response.bytes_stream();
-        let mut file = fs::File::create(&destination).await?;
-        let mut downloaded: u64 = 0;
-
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            downloaded += chunk.len() as u64;
-
-            if is_cancelled(&cancel_check) {
-                return Err(anyhow!("Download cancelled"));
-            }
-
-            file.write_all(&chunk).await?;
-            if let Some(ref tracker) = progress {
-                tracker.update_file_absolute(&file_info.path, downloaded, expected_total, false);
-            }
-        }
-
-        file.flush().await?;
-
-        if let Some(ref tracker) = progress {
-            tracker.update_file_absolute(&file_info.path, downloaded, expected_total, true);
-        }
+        // Only attempt a Range-resumed download if the server told us (via
+        // the HEAD response) that it honors them; otherwise every attempt
+        // starts over from byte zero.
+        let accept_ranges = head_response
+            .headers()
+            .get(reqwest::header::ACCEPT_RANGES)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.eq_ignore_ascii_case("bytes"))
+            .unwrap_or(false);
+
+        // Regular HTTP download (fallback or primary), resumable across retries.
+        self.download_http_with_resume(
+            &download_url,
+            &destination,
+            file_info,
+            xet_file_data.as_ref().map(|d| d.file_hash.as_str()),
+            accept_ranges,
+            resume,
+            &cancel_check,
+            &progress,
+        )
+        .await?;
+
+        revalidation::save(
+            &destination,
+            &revalidation::metadata_from_headers(head_response.headers(), None),
+        );
 
         Ok(destination.to_string_lossy().to_string())
     }
@@ -539,15 +1228,508 @@ impl HfAdapter {
             )
             .await?;
 
+        // xet-core verifies chunk hashes internally as it reconstructs the
+        // file, but under `VerifyMode::Full` we re-check the materialized
+        // result against the CAS hash here too, for the same reason the
+        // plain-HTTP path does: catch corruption introduced on the way to
+        // or within the local filesystem, not just in transit.
+        if self.verify_mode == VerifyMode::Full {
+            if let Err(err) = integrity::verify_xet_merkle(dest_path, &xet_file_data.file_hash).await {
+                let _ = fs::remove_file(dest_path).await;
+                return Err(err);
+            }
+        }
+
         if let Some(ref tracker) = progress {
             tracker.update_file_absolute(file_name, expected_size, expected_size, true);
         }
 
         Ok(dest_path.to_string_lossy().to_string())
     }
+
+    /// Download `download_url` into `destination` via the plain HTTP path,
+    /// resuming from a `.part` sidecar across retries and verifying the
+    /// result against `expected_xet_hash` (when known) before the final
+    /// atomic rename. `accept_ranges` gates whether a non-empty `.part` is
+    /// resumed with a `Range` request or discarded and restarted from zero.
+    /// `resume` gates whether a `.part` left over from a previous attempt
+    /// (of this call, or an earlier cancelled/crashed process) is verified
+    /// against its checkpoint sidecar and kept on failure for a later retry
+    /// to pick up — see `checkpoint.rs`.
+    #[allow(clippy::too_many_arguments)]
+    async fn download_http_with_resume(
+        &self,
+        download_url: &str,
+        destination: &Path,
+        file_info: &HfFileInfo,
+        expected_xet_hash: Option<&str>,
+        accept_ranges: bool,
+        resume: bool,
+        cancel_check: &Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: &Option<OperationProgress>,
+    ) -> Result<()> {
+        let part_path = part_path_for(destination);
+
+        let result = retry::retry(
+            &self.retry_config,
+            cancel_check,
+            |attempt, delay| {
+                debug!(
+                    "[RETRY] download of {} attempt {} backing off {:?}",
+                    file_info.path, attempt, delay
+                );
+                if let Some(ref tracker) = progress {
+                    tracker.set_phase(XetProgressPhase::Retrying, true);
+                    if !accept_ranges {
+                        tracker.reset_file_progress(&file_info.path);
+                    }
+                    tracker.set_phase(XetProgressPhase::Downloading, true);
+                }
+            },
+            |_attempt| async {
+                match self
+                    .download_http_once(
+                        download_url,
+                        &part_path,
+                        file_info,
+                        accept_ranges,
+                        resume,
+                        cancel_check,
+                        progress,
+                    )
+                    .await
+                {
+                    Ok(resumed) => retry::RetryResult::Success(resumed),
+                    Err(err) if is_transient_download_error(&err) => retry::RetryResult::Retry(err),
+                    Err(err) => retry::RetryResult::Err(err),
+                }
+            },
+        )
+        .await;
+
+        let resumed = match result {
+            Ok(resumed) => resumed,
+            Err(err) => {
+                // A resumable attempt keeps its `.part` (and checkpoint
+                // sidecar) around so the next call picks up where this one
+                // left off instead of re-downloading the whole file.
+                if !resume {
+                    let _ = fs::remove_file(&part_path).await;
+                    checkpoint::remove(&part_path);
+                }
+                return Err(err);
+            }
+        };
+
+        if self.verify_mode != VerifyMode::Off {
+            if let Some(expected_hash) = expected_xet_hash {
+                if let Err(err) = integrity::verify_xet_merkle(&part_path, expected_hash).await {
+                    if !resume {
+                        let _ = fs::remove_file(&part_path).await;
+                    }
+                    checkpoint::remove(&part_path);
+                    return Err(err);
+                }
+            } else if resumed
+                && self.verify_mode == VerifyMode::Full
+                && integrity::is_git_blob_sha1(&file_info.hash)
+            {
+                // The streaming `ContentVerifier` was disabled for this
+                // attempt (it can't account for bytes an earlier attempt
+                // already wrote), so a resumed completion needs this
+                // buffered re-read to still get checked under Full mode.
+                if let Err(err) =
+                    integrity::verify_git_blob_sha1(&part_path, file_info.size, &file_info.hash).await
+                {
+                    if !resume {
+                        let _ = fs::remove_file(&part_path).await;
+                    }
+                    checkpoint::remove(&part_path);
+                    return Err(err);
+                }
+            }
+        }
+
+        checkpoint::remove(&part_path);
+
+        fs::rename(&part_path, destination)
+            .await
+            .context("failed to move completed download into place")?;
+
+        Ok(())
+    }
+
+    /// Perform a single download attempt, resuming from any bytes already
+    /// present in `part_path` via a `Range` request when `accept_ranges` is
+    /// set, or truncating and restarting from zero otherwise. When `resume`
+    /// is also set, the resume point is the last checkpoint-verified offset
+    /// (see `checkpoint.rs`) rather than the raw `.part` length, and every
+    /// full `CHECKPOINT_CHUNK_SIZE` block written is recorded as it lands.
+    /// Returns whether this attempt actually resumed (`206 Partial Content`
+    /// off a non-empty `.part`) so the caller knows its streaming
+    /// `ContentVerifier` was skipped and a buffered check is still owed.
+    #[allow(clippy::too_many_arguments)]
+    async fn download_http_once(
+        &self,
+        download_url: &str,
+        part_path: &Path,
+        file_info: &HfFileInfo,
+        accept_ranges: bool,
+        resume: bool,
+        cancel_check: &Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: &Option<OperationProgress>,
+    ) -> Result<bool> {
+        let already_written = if accept_ranges {
+            let on_disk = fs::metadata(part_path).await.map(|m| m.len()).unwrap_or(0);
+            if resume {
+                let verified = checkpoint::verified_resume_offset(part_path);
+                if verified < on_disk {
+                    if let Ok(file) = fs::OpenOptions::new().write(true).open(part_path).await {
+                        let _ = file.set_len(verified).await;
+                    }
+                }
+                verified
+            } else {
+                on_disk
+            }
+        } else {
+            0
+        };
+
+        let mut request = self.client.get(download_url);
+        if already_written > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", already_written));
+        }
+
+        let response = request.send().await.context("download request failed")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("download request failed: HTTP {}", response.status()));
+        }
+
+        // The server may ignore the Range header (no `Accept-Ranges: bytes`
+        // support); in that case it answers with `200` instead of `206` and
+        // sends the whole body, so we must restart from zero.
+        let resumed = already_written > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+        let starting_offset = if resumed { already_written } else { 0 };
+
+        // Starting over from zero invalidates any checkpoint recorded for a
+        // previous attempt's bytes, which `DownloadWriter::create` is about
+        // to overwrite.
+        if resume && !resumed {
+            checkpoint::remove(part_path);
+        }
+
+        let content_length = response.content_length().unwrap_or(0);
+        let expected_total = if resumed {
+            starting_offset + content_length
+        } else {
+            response.content_length().unwrap_or(file_info.size)
+        };
+
+        if let Some(ref tracker) = progress {
+            tracker.ensure_file_entry(&file_info.path, expected_total);
+            if starting_offset > 0 {
+                tracker.update_file_absolute(&file_info.path, starting_offset, expected_total, false);
+            }
+        }
+
+        let mut file = if resumed {
+            DownloadWriter::open_append(part_path, self.io_writer_config).await?
+        } else {
+            DownloadWriter::create(part_path, self.io_writer_config).await?
+        };
+
+        let mut stream = response.bytes_stream();
+        let mut downloaded = starting_offset;
+
+        // A resumed write only sees the *new* bytes here, so a streaming
+        // hasher would miss whatever was already on disk; skip it and let
+        // the caller's buffered re-read of the full file (the xet-hash
+        // check, or the git-blob check under `VerifyMode::Full`) cover that
+        // case instead.
+        let mut verifier = if resumed {
+            ContentVerifier::Disabled
+        } else {
+            ContentVerifier::for_download(self.verify_mode, file_info)
+        };
+
+        // Buffers whole `CHECKPOINT_CHUNK_SIZE` blocks so each can be
+        // recorded in the checkpoint sidecar as soon as it's flushed —
+        // `starting_offset` is always block-aligned (see
+        // `checkpoint::verified_resume_offset`), so this never needs to
+        // record a partial leading block.
+        let mut checkpoint_block_offset = starting_offset;
+        let mut checkpoint_buf: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.context("download stream errored")?;
+            downloaded += chunk.len() as u64;
+
+            if is_cancelled(cancel_check) {
+                return Err(anyhow!("Download cancelled"));
+            }
+
+            file.write_all(&chunk).await?;
+            verifier.update(&chunk);
+            if let Some(ref tracker) = progress {
+                tracker.update_file_absolute(&file_info.path, downloaded, expected_total, false);
+            }
+
+            if resume {
+                checkpoint_buf.extend_from_slice(&chunk);
+                while checkpoint_buf.len() as u64 >= checkpoint::CHECKPOINT_CHUNK_SIZE {
+                    let block: Vec<u8> = checkpoint_buf
+                        .drain(..checkpoint::CHECKPOINT_CHUNK_SIZE as usize)
+                        .collect();
+                    checkpoint::append_block(part_path, checkpoint_block_offset, &block);
+                    checkpoint_block_offset += block.len() as u64;
+                }
+            }
+        }
+
+        file.flush().await?;
+        verifier.finish()?;
+
+        if let Some(ref tracker) = progress {
+            tracker.update_file_absolute(&file_info.path, downloaded, expected_total, true);
+        }
+
+        Ok(resumed)
+    }
 }
 
-fn determine_destination(
+#[async_trait]
+impl ModelDownloader for HfAdapter {
+    async fn list_files(&self, repo_id: &str, revision: Option<&str>) -> Result<Vec<HfFileInfo>> {
+        HfAdapter::list_files(self, repo_id, revision).await
+    }
+
+    async fn download_file_with_cancel(
+        &self,
+        repo_id: &str,
+        filename: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: Option<&str>,
+        force_revalidate: bool,
+        resume: bool,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        HfAdapter::download_file_with_cancel(
+            self,
+            repo_id,
+            filename,
+            repo_type,
+            revision,
+            local_dir,
+            force_revalidate,
+            resume,
+            cancel_check,
+            progress,
+        )
+        .await
+    }
+
+    async fn download_snapshot(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: &str,
+        allow_patterns: Option<Vec<String>>,
+        ignore_patterns: Option<Vec<String>>,
+        force_revalidate: bool,
+        resume: bool,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        HfAdapter::download_snapshot(
+            self,
+            repo_id,
+            repo_type,
+            revision,
+            local_dir,
+            allow_patterns,
+            ignore_patterns,
+            force_revalidate,
+            resume,
+            cancel_check,
+            progress,
+        )
+        .await
+    }
+
+    async fn upload_file(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_path: &str,
+        remote_path: &str,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        HfAdapter::upload_file(
+            self,
+            repo_id,
+            repo_type,
+            revision,
+            local_path,
+            remote_path,
+            cancel_check,
+            progress,
+        )
+        .await
+    }
+
+    async fn upload_snapshot(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: &str,
+        allow_patterns: Option<Vec<String>>,
+        ignore_patterns: Option<Vec<String>>,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        HfAdapter::upload_snapshot(
+            self,
+            repo_id,
+            repo_type,
+            revision,
+            local_dir,
+            allow_patterns,
+            ignore_patterns,
+            cancel_check,
+            progress,
+        )
+        .await
+    }
+}
+
+/// Tiny dependency-free `[0, bound)` PRNG seeded from the system clock;
+/// sufficient for decorrelating retries, not for anything security-sensitive.
+fn rand_below(bound: u64) -> u64 {
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    seed.wrapping_mul(6364136223846793005).wrapping_add(1) % bound
+}
+
+/// Whether an HTTP status is worth retrying: `408`/`429`, or any `5xx`.
+/// Every other non-success status (auth, not-found, bad-request, ...) is
+/// permanent and retrying it would just waste round trips.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429) || status.is_server_error()
+}
+
+/// Whether a transport-level `reqwest::Error` looks transient (timed out,
+/// failed to connect, or died mid-request) as opposed to a permanent one
+/// (e.g. a malformed request that will fail identically every time).
+fn is_transient_reqwest_error(err: &reqwest::Error) -> bool {
+    err.is_timeout() || err.is_connect() || err.is_request()
+}
+
+/// How long `send_with_retry` should wait before attempt `attempt + 1`.
+/// A `Retry-After` value from the server is honored as a floor; otherwise
+/// falls back to exponential backoff (`RETRY_BACKOFF_BASE_MS * 2^attempt`,
+/// capped at `RETRY_BACKOFF_MAX_MS`) with full jitter in `[0, backoff]` so
+/// that a burst of concurrent callers doesn't retry in lockstep.
+fn retry_delay(attempt: usize, retry_after: Option<Duration>) -> Duration {
+    if let Some(delay) = retry_after {
+        return delay;
+    }
+    let exp_ms = RETRY_BACKOFF_BASE_MS.saturating_mul(1u64 << attempt.min(16));
+    let capped_ms = exp_ms.min(RETRY_BACKOFF_MAX_MS);
+    Duration::from_millis(rand_below(capped_ms.max(1)))
+}
+
+/// Parse an RFC 9110 `Retry-After` header value: either delta-seconds
+/// (`"120"`) or an HTTP-date (`"Sun, 06 Nov 1994 08:49:37 GMT"`).
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let target = parse_http_date(value)?;
+    target.duration_since(std::time::SystemTime::now()).ok()
+}
+
+/// Parse an IMF-fixdate (e.g. `"Sun, 06 Nov 1994 08:49:37 GMT"`) into a
+/// `SystemTime`. Dependency-free: assumes GMT and the fixed month
+/// abbreviations `Retry-After` always uses.
+fn parse_http_date(value: &str) -> Option<std::time::SystemTime> {
+    let rest = value.splitn(2, ", ").nth(1)?;
+    let mut parts = rest.split_whitespace();
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month = month_number(parts.next()?)?;
+    let year: i64 = parts.next()?.parse().ok()?;
+    let mut time_parts = parts.next()?.split(':');
+    let hour: i64 = time_parts.next()?.parse().ok()?;
+    let minute: i64 = time_parts.next()?.parse().ok()?;
+    let second: i64 = time_parts.next()?.parse().ok()?;
+
+    let days = days_from_civil(year, month, day);
+    let epoch_secs = days * 86_400 + hour * 3600 + minute * 60 + second;
+    if epoch_secs < 0 {
+        return None;
+    }
+    Some(std::time::UNIX_EPOCH + Duration::from_secs(epoch_secs as u64))
+}
+
+fn month_number(name: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    MONTHS.iter().position(|&m| m == name).map(|i| i as i64 + 1)
+}
+
+/// Days since the Unix epoch for a proleptic-Gregorian `(year, month, day)`,
+/// via Howard Hinnant's `days_from_civil` algorithm.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Whether `err` represents a transient failure worth retrying (connection
+/// resets, timeouts, 5xx/408/429) as opposed to a permanent one (404, auth).
+/// Whether `err` is the kind of failure `download_http_with_resume`'s retry
+/// loop treats as transient (and so retries instead of giving up
+/// immediately) — reused by [`crate::error::XetError::from_anyhow`] to
+/// classify an error that exhausted every retry as a `NetworkError` rather
+/// than `Unknown`.
+pub(crate) fn is_transient_download_error(err: &anyhow::Error) -> bool {
+    if err.downcast_ref::<ChecksumMismatchError>().is_some() {
+        return false;
+    }
+    if let Some(reqwest_err) = err.downcast_ref::<reqwest::Error>() {
+        return reqwest_err.is_timeout() || reqwest_err.is_connect() || reqwest_err.is_request();
+    }
+    let message = err.to_string();
+    message.contains("HTTP 5")
+        || message.contains("HTTP 408")
+        || message.contains("HTTP 429")
+        || message.contains("stream errored")
+}
+
+/// Sidecar path for an in-progress download.
+pub(crate) fn part_path_for(destination: &Path) -> PathBuf {
+    let mut part = destination.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+pub(crate) fn determine_destination(
     local_dir: Option<&str>,
     cache_dir: Option<&Path>,
     repo_id: &str,
@@ -571,9 +1753,67 @@ fn determine_destination(
     PathBuf::from(filename)
 }
 
-fn is_cancelled(cancel_check: &Option<Arc<dyn Fn() -> bool + Send + Sync>>) -> bool {
+/// Resolve the `Authorization` header value for `url`, preferring a
+/// per-host credential over the single fallback `token`.
+fn auth_header_for_url(
+    credential_provider: &Option<Arc<dyn CredentialProvider>>,
+    url: &str,
+    token: Option<&str>,
+) -> Option<String> {
+    if let Some(provider) = credential_provider {
+        if let Some(host) = host_of(url) {
+            if let Some(credential) = provider.credentials_for_host(&host) {
+                if let Some(header) = credential.to_header_value() {
+                    return Some(header);
+                }
+            }
+        }
+    }
+    token.map(|t| format!("Bearer {}", t))
+}
+
+fn endpoint_auth_header(
+    credential_provider: &Option<Arc<dyn CredentialProvider>>,
+    endpoint: &str,
+    token: Option<&str>,
+) -> Option<String> {
+    auth_header_for_url(credential_provider, endpoint, token)
+}
+
+pub(crate) fn is_cancelled(cancel_check: &Option<Arc<dyn Fn() -> bool + Send + Sync>>) -> bool {
     cancel_check
         .as_ref()
         .map(|cancel| cancel())
         .unwrap_or(false)
+}
+
+/// Recursively list every regular file under `root`, returning each one's
+/// path relative to `root` (forward-slash separated, matching HF repo paths)
+/// alongside its size. Walks iteratively with an explicit stack rather than
+/// recursive `async fn` calls.
+async fn collect_local_files(root: &Path) -> Result<Vec<(String, u64)>> {
+    let mut out = Vec::new();
+    let mut stack = vec![PathBuf::new()];
+
+    while let Some(relative_dir) = stack.pop() {
+        let dir = root.join(&relative_dir);
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .with_context(|| format!("failed to read directory {dir:?} for upload"))?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            let relative = relative_dir.join(entry.file_name());
+
+            if file_type.is_dir() {
+                stack.push(relative);
+            } else if file_type.is_file() {
+                let size = entry.metadata().await?.len();
+                let path = relative.to_string_lossy().replace('\\', "/");
+                out.push((path, size));
+            }
+        }
+    }
+
+    Ok(out)
 }
\ No newline at end of file
@@ -0,0 +1,288 @@
+// Wire-format helpers for HF's "commit" API: the preupload negotiation that
+// decides inline-git vs LFS/XET per file, the plain LFS batch-upload
+// fallback for files XET won't take, and the NDJSON commit payload that
+// registers the result. `HfAdapter::upload_file`/`upload_snapshot` own the
+// HTTP orchestration; this module is just the request/response shapes and
+// pure serialization, mirroring `revalidation.rs`'s split on the download
+// side.
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// A file about to be offered to `POST .../preupload/{revision}`.
+pub(crate) struct PendingUpload<'a> {
+    pub(crate) path: &'a str,
+    pub(crate) size: u64,
+    pub(crate) sha256_hex: &'a str,
+    pub(crate) sample_base64: &'a str,
+}
+
+#[derive(Serialize)]
+struct PreuploadFileRequest<'a> {
+    path: &'a str,
+    size: u64,
+    sample: &'a str,
+    sha: &'a str,
+}
+
+#[derive(Serialize)]
+struct PreuploadRequest<'a> {
+    files: Vec<PreuploadFileRequest<'a>>,
+}
+
+/// Build the JSON body for `POST .../preupload/{revision}`.
+pub(crate) fn preupload_request_body(files: &[PendingUpload<'_>]) -> serde_json::Result<String> {
+    let request = PreuploadRequest {
+        files: files
+            .iter()
+            .map(|f| PreuploadFileRequest {
+                path: f.path,
+                size: f.size,
+                sample: f.sample_base64,
+                sha: f.sha256_hex,
+            })
+            .collect(),
+    };
+    serde_json::to_string(&request)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct PreuploadFileResponse {
+    #[allow(dead_code)]
+    pub(crate) path: String,
+    #[serde(rename = "uploadMode")]
+    pub(crate) upload_mode: String,
+    #[serde(default, rename = "shouldIgnore")]
+    pub(crate) should_ignore: bool,
+}
+
+impl PreuploadFileResponse {
+    pub(crate) fn is_lfs(&self) -> bool {
+        self.upload_mode == "lfs"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PreuploadResponse {
+    pub(crate) files: Vec<PreuploadFileResponse>,
+}
+
+#[derive(Serialize)]
+struct LfsBatchRequest<'a> {
+    operation: &'a str,
+    transfers: [&'a str; 1],
+    objects: Vec<LfsBatchObjectRequest<'a>>,
+}
+
+#[derive(Serialize)]
+struct LfsBatchObjectRequest<'a> {
+    oid: &'a str,
+    size: u64,
+}
+
+/// Build the JSON body for a Git LFS `batch` API `upload` request, per the
+/// [LFS batch API spec](https://github.com/git-lfs/git-lfs/blob/main/docs/api/batch.md).
+pub(crate) fn lfs_batch_request_body(objects: &[(&str, u64)]) -> serde_json::Result<String> {
+    let request = LfsBatchRequest {
+        operation: "upload",
+        transfers: ["basic"],
+        objects: objects
+            .iter()
+            .map(|(oid, size)| LfsBatchObjectRequest { oid, size: *size })
+            .collect(),
+    };
+    serde_json::to_string(&request)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LfsBatchResponse {
+    pub(crate) objects: Vec<LfsBatchObjectResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LfsBatchObjectResponse {
+    pub(crate) oid: String,
+    #[serde(default)]
+    pub(crate) actions: Option<LfsActions>,
+    #[serde(default)]
+    pub(crate) error: Option<LfsBatchError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LfsActions {
+    #[serde(default)]
+    pub(crate) upload: Option<LfsAction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct LfsAction {
+    pub(crate) href: String,
+    #[serde(default)]
+    pub(crate) header: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LfsBatchError {
+    pub(crate) code: u32,
+    pub(crate) message: String,
+}
+
+/// One resolved file ready to register in a commit, after preupload/LFS/XET
+/// negotiation has decided how (or whether) its content was stored.
+pub(crate) enum CommitFile {
+    /// Small/text content inlined directly in the commit payload.
+    Inline {
+        path: String,
+        content_base64: String,
+    },
+    /// Content already uploaded to LFS/XET CAS, referenced by its oid.
+    Lfs { path: String, oid: String, size: u64 },
+}
+
+#[derive(Serialize)]
+struct CommitHeaderLine {
+    key: &'static str,
+    value: CommitHeaderValue,
+}
+
+#[derive(Serialize)]
+struct CommitHeaderValue {
+    summary: String,
+}
+
+#[derive(Serialize)]
+struct CommitFileLine {
+    key: &'static str,
+    value: CommitFileValue,
+}
+
+#[derive(Serialize)]
+struct CommitFileValue {
+    path: String,
+    content: String,
+    encoding: &'static str,
+}
+
+#[derive(Serialize)]
+struct CommitLfsFileLine {
+    key: &'static str,
+    value: CommitLfsFileValue,
+}
+
+#[derive(Serialize)]
+struct CommitLfsFileValue {
+    path: String,
+    oid: String,
+    size: u64,
+    algo: &'static str,
+}
+
+/// Build the newline-delimited-JSON body for `POST .../commit/{revision}`: a
+/// `header` line with the commit summary, followed by one `file`/`lfsFile`
+/// line per resolved upload.
+pub(crate) fn build_commit_ndjson(summary: &str, files: &[CommitFile]) -> serde_json::Result<String> {
+    let mut lines = Vec::with_capacity(files.len() + 1);
+    lines.push(serde_json::to_string(&CommitHeaderLine {
+        key: "header",
+        value: CommitHeaderValue {
+            summary: summary.to_string(),
+        },
+    })?);
+
+    for file in files {
+        let line = match file {
+            CommitFile::Inline {
+                path,
+                content_base64,
+            } => serde_json::to_string(&CommitFileLine {
+                key: "file",
+                value: CommitFileValue {
+                    path: path.clone(),
+                    content: content_base64.clone(),
+                    encoding: "base64",
+                },
+            })?,
+            CommitFile::Lfs { path, oid, size } => serde_json::to_string(&CommitLfsFileLine {
+                key: "lfsFile",
+                value: CommitLfsFileValue {
+                    path: path.clone(),
+                    oid: oid.clone(),
+                    size: *size,
+                    algo: "sha256",
+                },
+            })?,
+        };
+        lines.push(line);
+    }
+
+    Ok(lines.join("\n"))
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CommitResponse {
+    #[serde(default, rename = "commitOid")]
+    pub(crate) commit_oid: Option<String>,
+}
+
+/// SHA-256 hex digest of `bytes`, used both as the preupload `sha` and as
+/// the LFS object `oid`.
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    let digest = hasher.finalize();
+
+    let mut out = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        let _ = write!(out, "{:02x}", byte);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_hex_matches_known_vector() {
+        assert_eq!(
+            sha256_hex(b"hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[test]
+    fn preupload_body_serializes_path_and_sha() {
+        let files = vec![PendingUpload {
+            path: "weights.bin",
+            size: 42,
+            sha256_hex: "deadbeef",
+            sample_base64: "c2FtcGxl",
+        }];
+        let body = preupload_request_body(&files).unwrap();
+        assert!(body.contains("\"path\":\"weights.bin\""));
+        assert!(body.contains("\"sha\":\"deadbeef\""));
+    }
+
+    #[test]
+    fn commit_ndjson_has_one_line_per_operation() {
+        let files = vec![
+            CommitFile::Inline {
+                path: "README.md".to_string(),
+                content_base64: "aGVsbG8=".to_string(),
+            },
+            CommitFile::Lfs {
+                path: "weights.bin".to_string(),
+                oid: "deadbeef".to_string(),
+                size: 42,
+            },
+        ];
+        let body = build_commit_ndjson("Upload 2 files", &files).unwrap();
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains("\"header\""));
+        assert!(lines[1].contains("\"file\""));
+        assert!(lines[2].contains("\"lfsFile\""));
+    }
+}
@@ -0,0 +1,72 @@
+// Transport-level configuration for the HTTP clients the adapter builds.
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Proxy, TLS, timeout, and default-header knobs for the reqwest clients
+/// used to talk to the HF API and the CAS reconstruction endpoint, so
+/// embedders can reach private/self-hosted mirrors without patching the
+/// crate.
+#[derive(Debug, Clone, Default)]
+pub struct HttpClientConfig {
+    pub proxy_url: Option<String>,
+    pub proxy_username: Option<String>,
+    pub proxy_password: Option<String>,
+    pub extra_root_cert_paths: Vec<PathBuf>,
+    pub danger_accept_invalid_certs: bool,
+    pub connect_timeout: Option<Duration>,
+    pub request_timeout: Option<Duration>,
+    pub default_headers: Vec<(String, String)>,
+}
+
+impl HttpClientConfig {
+    /// Apply this configuration to a `reqwest::ClientBuilder`.
+    pub fn apply(&self, mut builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder> {
+        if let Some(ref proxy_url) = self.proxy_url {
+            let mut proxy = reqwest::Proxy::all(proxy_url)
+                .with_context(|| format!("invalid proxy URL: {}", proxy_url))?;
+            if let (Some(user), Some(pass)) = (&self.proxy_username, &self.proxy_password) {
+                proxy = proxy.basic_auth(user, pass);
+            }
+            builder = builder.proxy(proxy);
+        }
+
+        for path in &self.extra_root_cert_paths {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("reading CA certificate at {:?}", path))?;
+            let cert = reqwest::Certificate::from_pem(&pem)
+                .with_context(|| format!("parsing CA certificate at {:?}", path))?;
+            builder = builder.add_root_certificate(cert);
+        }
+
+        if self.danger_accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        Ok(builder)
+    }
+
+    /// Merge this config's extra default headers on top of `base` (e.g. an
+    /// already-resolved `Authorization` header), without dropping it.
+    pub fn merged_headers(
+        &self,
+        mut base: reqwest::header::HeaderMap,
+    ) -> Result<reqwest::header::HeaderMap> {
+        for (key, value) in &self.default_headers {
+            let name = reqwest::header::HeaderName::from_bytes(key.as_bytes())
+                .with_context(|| format!("invalid header name: {}", key))?;
+            let value = reqwest::header::HeaderValue::from_str(value)
+                .with_context(|| format!("invalid header value for {}: {}", key, value))?;
+            base.insert(name, value);
+        }
+        Ok(base)
+    }
+}
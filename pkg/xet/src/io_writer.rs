@@ -0,0 +1,205 @@
+// Optional io_uring-backed write path for downloaded file chunks, behind the
+// `io_uring` Cargo feature. Plain `tokio::fs::File` writes (the `Std`
+// variant) go through a blocking syscall per chunk that ties up a runtime
+// worker thread; under a high `max_concurrent_downloads` that contention
+// shows up as stalls even though the network side of every transfer is
+// fully concurrent. The io_uring variant submits each write through a
+// per-file submission/completion ring instead. Writes are submitted one at
+// a time, not batched, and the calling worker thread waits for each
+// completion inside `block_in_place`, so that thread is blocked for the
+// wait while the runtime moves its other tasks to other workers.
+//
+// Falls back to `Std` automatically off Linux, when the feature is
+// disabled, when `XetConfig`'s force-disable flag is set, or when ring
+// setup fails at runtime (e.g. a kernel built without `CONFIG_IO_URING`, or
+// a seccomp profile that blocks the `io_uring_setup` syscall) —
+// `DownloadWriter::create`/`open_append` only fail because the underlying
+// file operation itself failed, never because io_uring wasn't available.
+//
+// Idea source: actix-files' io_uring file-serving implementation.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+/// Per-client knobs for [`DownloadWriter`], surfaced via `XetConfig`.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct IoWriterConfig {
+    /// Force-disable the io_uring writer even when the feature is compiled
+    /// in and the kernel supports it.
+    pub(crate) force_disable_io_uring: bool,
+}
+
+/// A file handle for a single download's write path, backed by whichever
+/// implementation is available; see the module doc comment.
+pub(crate) enum DownloadWriter {
+    Std(fs::File),
+    #[cfg(all(target_os = "linux", feature = "io_uring"))]
+    IoUring(io_uring_impl::IoUringFile),
+}
+
+impl DownloadWriter {
+    /// Create (truncating) `path` for a from-scratch download.
+    pub(crate) async fn create(path: &Path, config: IoWriterConfig) -> Result<Self> {
+        #[cfg(all(target_os = "linux", feature = "io_uring"))]
+        if !config.force_disable_io_uring {
+            if let Some(writer) = io_uring_impl::IoUringFile::create(path).await {
+                return Ok(Self::IoUring(writer));
+            }
+        }
+        let _ = config;
+
+        Ok(Self::Std(
+            fs::File::create(path)
+                .await
+                .context("failed to create download file")?,
+        ))
+    }
+
+    /// Open `path` for append, for a resumed download continuing past
+    /// whatever bytes are already on disk.
+    pub(crate) async fn open_append(path: &Path, config: IoWriterConfig) -> Result<Self> {
+        #[cfg(all(target_os = "linux", feature = "io_uring"))]
+        if !config.force_disable_io_uring {
+            if let Some(writer) = io_uring_impl::IoUringFile::open_append(path).await {
+                return Ok(Self::IoUring(writer));
+            }
+        }
+        let _ = config;
+
+        Ok(Self::Std(
+            fs::OpenOptions::new()
+                .append(true)
+                .open(path)
+                .await
+                .context("failed to open download file for append")?,
+        ))
+    }
+
+    /// Write `data`. For the io_uring variant this returns only once the
+    /// write's completion has been reaped from the completion queue; for
+    /// `Std`, `tokio::fs::File` may still be finishing the write in the
+    /// background until the next operation or `flush`. Neither variant
+    /// syncs the data to disk.
+    pub(crate) async fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        match self {
+            Self::Std(file) => file.write_all(data).await.context("write failed"),
+            #[cfg(all(target_os = "linux", feature = "io_uring"))]
+            Self::IoUring(writer) => writer.write_all(data).await,
+        }
+    }
+
+    pub(crate) async fn flush(&mut self) -> Result<()> {
+        match self {
+            Self::Std(file) => file.flush().await.context("flush failed"),
+            #[cfg(all(target_os = "linux", feature = "io_uring"))]
+            Self::IoUring(writer) => writer.flush().await,
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "io_uring"))]
+mod io_uring_impl {
+    use anyhow::{anyhow, Context, Result};
+    use io_uring::{opcode, types, IoUring};
+    use std::fs::{File, OpenOptions};
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    /// One file's io_uring submission/completion ring plus its current write
+    /// offset. A ring is cheap enough (a handful of pages) to own per file
+    /// rather than sharing one across every in-flight download, which keeps
+    /// one file's completions from backing up behind another's.
+    pub(super) struct IoUringFile {
+        file: File,
+        ring: IoUring,
+        offset: u64,
+    }
+
+    const RING_ENTRIES: u32 = 64;
+
+    impl IoUringFile {
+        /// `None` means "couldn't set up a ring" (missing kernel support, a
+        /// seccomp filter, etc.) — the caller falls back to `Std`.
+        pub(super) async fn create(path: &Path) -> Option<Self> {
+            let path = path.to_path_buf();
+            tokio::task::spawn_blocking(move || Self::open(&path, true))
+                .await
+                .ok()
+                .flatten()
+        }
+
+        pub(super) async fn open_append(path: &Path) -> Option<Self> {
+            let path = path.to_path_buf();
+            tokio::task::spawn_blocking(move || Self::open(&path, false))
+                .await
+                .ok()
+                .flatten()
+        }
+
+        fn open(path: &Path, truncate: bool) -> Option<Self> {
+            let file = if truncate {
+                File::create(path).ok()?
+            } else {
+                OpenOptions::new().append(true).open(path).ok()?
+            };
+            let offset = file.metadata().ok()?.len();
+            let ring = IoUring::new(RING_ENTRIES).ok()?;
+            Some(Self { file, ring, offset })
+        }
+
+        /// Submit one write at the file's current offset and block (via
+        /// `block_in_place`, not a spawned task, so the borrowed `data`
+        /// doesn't need to be `'static`) until its completion is reaped.
+        pub(super) async fn write_all(&mut self, data: &[u8]) -> Result<()> {
+            let Self { file, ring, offset } = self;
+            let fd = types::Fd(file.as_raw_fd());
+            let write_offset = *offset;
+            let len = data.len();
+
+            tokio::task::block_in_place(|| {
+                let write_e = opcode::Write::new(fd, data.as_ptr(), len as u32)
+                    .offset(write_offset)
+                    .build();
+
+                unsafe {
+                    ring.submission()
+                        .push(&write_e)
+                        .map_err(|_| anyhow!("io_uring submission queue full"))?;
+                }
+                ring.submit_and_wait(1)
+                    .context("io_uring submit_and_wait failed")?;
+
+                let cqe = ring
+                    .completion()
+                    .next()
+                    .ok_or_else(|| anyhow!("io_uring completion queue empty after submit_and_wait"))?;
+
+                let written = cqe.result();
+                if written < 0 {
+                    return Err(anyhow!(
+                        "io_uring write failed: {}",
+                        std::io::Error::from_raw_os_error(-written)
+                    ));
+                }
+                if written as usize != len {
+                    return Err(anyhow!(
+                        "short io_uring write: wrote {written} of {len} bytes"
+                    ));
+                }
+
+                Ok(())
+            })?;
+
+            *offset += len as u64;
+            Ok(())
+        }
+
+        /// Every `write_all` already waits for its completion, so there's
+        /// nothing buffered on our side to flush.
+        pub(super) async fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+}
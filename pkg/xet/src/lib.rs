@@ -1,17 +1,41 @@
 // Module declarations - following hf_xet structure
+mod checkpoint;
+mod chunker;
+mod credentials;
 mod error;
 mod ffi;
 mod hf_adapter;
+mod hf_commit;
+mod http_config;
+mod integrity;
+mod io_writer;
 mod logging;
+mod model_downloader;
+mod network_policy;
+mod object_store_adapter;
 mod progress;
+mod retry;
+mod revalidation;
 mod runtime;
 mod xet_downloader;
+#[cfg(feature = "fuse")]
+mod xet_fuse;
 mod xet_integration;
+mod xet_uploader;
 
 // Public exports
+pub use credentials::{CredentialProvider, EnvCredentialProvider, HostCredential, NetrcCredentialProvider};
 pub use error::*;
 pub use ffi::*;
+pub use http_config::HttpClientConfig;
+pub use integrity::VerifyMode;
+pub use network_policy::NetworkPolicy;
+pub use object_store_adapter::ObjectStoreConfig;
 pub use progress::{XetProgressCallback, XetProgressPhase, XetProgressUpdate};
+pub use retry::RetryConfig;
+#[cfg(feature = "fuse")]
+pub use xet_fuse::{FileManifestEntry, RepoFuseMount, XetFuseMount};
+pub use xet_uploader::XetUploader;
 
 // Re-export runtime utilities
 pub use runtime::{block_on, get_runtime};
@@ -25,6 +49,14 @@ pub(crate) struct DownloadOptions<'a> {
     pub repo_type: Option<&'a str>,
     pub revision: Option<&'a str>,
     pub local_dir: Option<&'a str>,
+    /// Bypass the local freshness window and force a conditional
+    /// revalidation request even for an entry that isn't stale yet.
+    pub force_revalidate: bool,
+    /// Verify a leftover `.part` file (and its checkpoint sidecar, see
+    /// `checkpoint.rs`) from a previous cancelled/crashed attempt and
+    /// continue from the last verified offset instead of discarding it and
+    /// starting over.
+    pub resume: bool,
 }
 
 pub(crate) struct SnapshotOptions<'a> {
@@ -33,6 +65,10 @@ pub(crate) struct SnapshotOptions<'a> {
     pub local_dir: &'a str,
     pub allow_patterns: Option<Vec<String>>,
     pub ignore_patterns: Option<Vec<String>>,
+    /// See [`DownloadOptions::force_revalidate`].
+    pub force_revalidate: bool,
+    /// See [`DownloadOptions::resume`].
+    pub resume: bool,
 }
 
 #[derive(Default)]
@@ -54,8 +90,9 @@ impl OperationContext {
 }
 
 // Main client structure
+#[derive(Clone)]
 pub struct XetClient {
-    adapter: hf_adapter::HfAdapter,
+    adapter: Arc<dyn model_downloader::ModelDownloader>,
     progress: ProgressHandler,
 }
 
@@ -68,17 +105,105 @@ impl XetClient {
         max_concurrent: u32,
         enable_dedup: bool,
     ) -> Result<Self> {
-        // Initialize logging on first client creation
-        crate::logging::init_logging();
+        Self::new_with_credential_provider(
+            endpoint,
+            token,
+            cache_dir,
+            max_concurrent,
+            enable_dedup,
+            None,
+        )
+    }
 
-        let endpoint = endpoint.unwrap_or_else(|| "https://huggingface.co".to_string());
-        let adapter = hf_adapter::HfAdapter::new(
+    /// Create a new XET client with a pluggable per-host credential provider.
+    ///
+    /// The provider is consulted (by host) for every outbound request the
+    /// adapter makes, in addition to the single `token` fallback, so callers
+    /// can mirror from private endpoints or gated repos without hardcoding
+    /// one bearer token for every host.
+    pub fn new_with_credential_provider(
+        endpoint: Option<String>,
+        token: Option<String>,
+        cache_dir: Option<String>,
+        max_concurrent: u32,
+        enable_dedup: bool,
+        credential_provider: Option<Box<dyn CredentialProvider>>,
+    ) -> Result<Self> {
+        Self::new_advanced(
             endpoint,
             token,
             cache_dir,
-            max_concurrent as usize,
+            max_concurrent,
             enable_dedup,
-        )?;
+            credential_provider,
+            HttpClientConfig::default(),
+            None,
+            VerifyMode::default(),
+            RetryConfig::default(),
+            false,
+        )
+    }
+
+    /// Create a new XET client with full control over per-host credentials,
+    /// transport settings (proxy, custom CA roots, timeouts, extra headers),
+    /// an optional [`NetworkPolicy`] constraining which hosts the adapter
+    /// may contact (the HF API host, the `xet-auth` refresh route, and the
+    /// CAS endpoint it resolves to), a [`VerifyMode`] controlling how
+    /// strictly downloaded/cached content is checked against its expected
+    /// hash, and a [`RetryConfig`] bounding how whole-file-transfer retries
+    /// back off.
+    ///
+    /// `endpoint` also selects the backend by URI scheme: an `s3://bucket[/prefix]`
+    /// endpoint targets that bucket directly (credentials and region come
+    /// from the environment, see [`ObjectStoreConfig::from_uri`]) instead of
+    /// the default HuggingFace tree/resolve API.
+    ///
+    /// `force_disable_io_uring` overrides the optional io_uring-backed write
+    /// path (see `io_writer.rs`) back to plain blocking file I/O even when
+    /// the `io_uring` feature is compiled in and the kernel supports it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_advanced(
+        endpoint: Option<String>,
+        token: Option<String>,
+        cache_dir: Option<String>,
+        max_concurrent: u32,
+        enable_dedup: bool,
+        credential_provider: Option<Box<dyn CredentialProvider>>,
+        http_config: HttpClientConfig,
+        network_policy: Option<Arc<NetworkPolicy>>,
+        verify_mode: VerifyMode,
+        retry_config: RetryConfig,
+        force_disable_io_uring: bool,
+    ) -> Result<Self> {
+        // Initialize logging on first client creation
+        crate::logging::init_logging();
+
+        let endpoint = endpoint.unwrap_or_else(|| "https://huggingface.co".to_string());
+
+        let adapter: Arc<dyn model_downloader::ModelDownloader> =
+            match object_store_adapter::ObjectStoreConfig::from_uri(&endpoint)? {
+                Some(object_store_config) => Arc::new(object_store_adapter::ObjectStoreDownloader::new(
+                    object_store_config,
+                    cache_dir,
+                    max_concurrent as usize,
+                    http_config,
+                    force_disable_io_uring,
+                )?),
+                None => Arc::new(hf_adapter::HfAdapter::new(
+                    endpoint,
+                    token,
+                    cache_dir,
+                    max_concurrent as usize,
+                    enable_dedup,
+                    credential_provider,
+                    http_config,
+                    network_policy,
+                    verify_mode,
+                    retry_config,
+                    force_disable_io_uring,
+                )?),
+            };
+
         Ok(Self {
             adapter,
             progress: ProgressHandler::default(),
@@ -123,6 +248,8 @@ impl XetClient {
                 repo_type,
                 revision,
                 local_dir,
+                force_revalidate: false,
+                resume: false,
             },
             OperationContext::default(),
         )
@@ -148,6 +275,8 @@ impl XetClient {
                 options.repo_type,
                 options.revision,
                 options.local_dir,
+                options.force_revalidate,
+                options.resume,
                 cancel_check,
                 progress,
             )
@@ -172,6 +301,8 @@ impl XetClient {
                 local_dir,
                 allow_patterns,
                 ignore_patterns,
+                force_revalidate: false,
+                resume: false,
             },
             OperationContext::default(),
         )
@@ -197,6 +328,109 @@ impl XetClient {
                 options.local_dir,
                 options.allow_patterns,
                 options.ignore_patterns,
+                options.force_revalidate,
+                options.resume,
+                cancel_check,
+                progress,
+            )
+            .await
+    }
+
+    /// Upload a single local file, committing it in the backend's own
+    /// atomic unit of work (see [`model_downloader::ModelDownloader::upload_file`]).
+    pub async fn upload_file(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_path: &str,
+        remote_path: &str,
+    ) -> Result<String> {
+        self.upload_file_with_options(
+            repo_id,
+            repo_type,
+            revision,
+            local_path,
+            remote_path,
+            OperationContext::default(),
+        )
+        .await
+    }
+
+    pub(crate) async fn upload_file_with_options(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_path: &str,
+        remote_path: &str,
+        context: OperationContext,
+    ) -> Result<String> {
+        let OperationContext {
+            cancel_check,
+            progress,
+        } = context;
+
+        self.adapter
+            .upload_file(
+                repo_id,
+                repo_type,
+                revision,
+                local_path,
+                remote_path,
+                cancel_check,
+                progress,
+            )
+            .await
+    }
+
+    /// Upload every file under `local_dir` (after pattern filtering) as a
+    /// single commit (see [`model_downloader::ModelDownloader::upload_snapshot`]).
+    pub async fn upload_snapshot(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: &str,
+        allow_patterns: Option<Vec<String>>,
+        ignore_patterns: Option<Vec<String>>,
+    ) -> Result<String> {
+        self.upload_snapshot_with_options(
+            repo_id,
+            repo_type,
+            revision,
+            local_dir,
+            allow_patterns,
+            ignore_patterns,
+            OperationContext::default(),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn upload_snapshot_with_options(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: &str,
+        allow_patterns: Option<Vec<String>>,
+        ignore_patterns: Option<Vec<String>>,
+        context: OperationContext,
+    ) -> Result<String> {
+        let OperationContext {
+            cancel_check,
+            progress,
+        } = context;
+
+        self.adapter
+            .upload_snapshot(
+                repo_id,
+                repo_type,
+                revision,
+                local_dir,
+                allow_patterns,
+                ignore_patterns,
                 cancel_check,
                 progress,
             )
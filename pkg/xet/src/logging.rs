@@ -1,6 +1,7 @@
 // Logging module for XET bindings
 use std::env;
 use std::sync::Once;
+use std::time::Duration;
 use tracing::debug;
 use tracing_subscriber::filter::EnvFilter;
 use tracing_subscriber::layer::SubscriberExt;
@@ -9,6 +10,25 @@ use tracing_subscriber::Layer;
 
 static INIT: Once = Once::new();
 
+/// Output format for the log stream, selected via `XET_LOG_FORMAT`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LogFormat {
+    /// Human-readable, the default.
+    Text,
+    /// Newline-delimited JSON, one event object per line — suitable for log
+    /// aggregators (Loki, Datadog, CloudWatch, ...).
+    Json,
+}
+
+impl LogFormat {
+    fn from_env() -> Self {
+        match env::var("XET_LOG_FORMAT") {
+            Ok(value) if value.eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
 /// Initialize logging for the XET binding library
 pub fn init_logging() {
     INIT.call_once(|| {
@@ -22,18 +42,64 @@ pub fn init_logging() {
         // Now create filter from environment (will use RUST_LOG we just set)
         let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
 
-        // For simplicity, we'll just use the human-readable format
-        // JSON format would require additional dependencies
-        let fmt_layer = tracing_subscriber::fmt::layer()
-            .with_target(false)
-            .with_filter(filter);
-
-        tracing_subscriber::registry().with(fmt_layer).init();
+        match LogFormat::from_env() {
+            LogFormat::Text => {
+                let fmt_layer = tracing_subscriber::fmt::layer()
+                    .with_target(false)
+                    .with_filter(filter);
+                tracing_subscriber::registry().with(fmt_layer).init();
+            }
+            LogFormat::Json => {
+                let fmt_layer = tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_target(false)
+                    .with_current_span(true)
+                    .with_span_list(false)
+                    .with_filter(filter);
+                tracing_subscriber::registry().with(fmt_layer).init();
+            }
+        }
 
         debug!("XET binding library initialized");
     });
 }
 
+/// Whether per-download completion metrics should be emitted as a dedicated
+/// `tracing` event, toggled via `XET_LOG_METRICS=1` (mirrors the
+/// request-logging toggle pict-rs exposes for its own completion log).
+/// Off by default so a plain download doesn't pay for the extra event.
+pub(crate) fn metrics_enabled() -> bool {
+    matches!(env::var("XET_LOG_METRICS").as_deref(), Ok("1") | Ok("true"))
+}
+
+/// Summary of a single completed (or failed) file download, emitted as one
+/// structured event so log aggregators can query/aggregate across
+/// downloads without parsing free-form messages.
+pub(crate) struct DownloadCompletionMetrics<'a> {
+    pub file_name: &'a str,
+    pub bytes: u64,
+    pub duration: Duration,
+    pub retries: usize,
+}
+
+/// Emit a `download.complete` event carrying [`DownloadCompletionMetrics`],
+/// gated behind [`metrics_enabled`]. Call this once per file, whether the
+/// download succeeded or ultimately failed after retries.
+pub(crate) fn log_download_completion(metrics: &DownloadCompletionMetrics<'_>) {
+    if !metrics_enabled() {
+        return;
+    }
+
+    tracing::info!(
+        event = "download.complete",
+        file = metrics.file_name,
+        bytes = metrics.bytes,
+        duration_ms = metrics.duration.as_millis() as u64,
+        retries = metrics.retries,
+        "XET download completed"
+    );
+}
+
 /// Log macros for convenience
 #[macro_export]
 macro_rules! xet_debug {
@@ -0,0 +1,385 @@
+// Backend-agnostic downloader surface so `XetClient` can target mirrors and
+// object stores beyond the HuggingFace tree/resolve API, selecting the
+// implementation by the endpoint's URI scheme. `HfAdapter` remains the
+// default implementation; see `object_store_adapter` for an S3-compatible
+// one.
+use crate::hf_adapter::HfFileInfo;
+use crate::progress::{OperationProgress, XetProgressPhase};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs;
+use tokio::sync::Semaphore;
+use tokio::time::sleep;
+
+/// Lists and downloads files from a model repository, independent of where
+/// it's actually hosted. `list_files`/`download_file_with_cancel`/
+/// `download_snapshot` mirror `HfAdapter`'s original public surface so that
+/// `XetClient` can hold any implementation behind a trait object.
+#[async_trait]
+pub(crate) trait ModelDownloader: Send + Sync {
+    async fn list_files(&self, repo_id: &str, revision: Option<&str>) -> Result<Vec<HfFileInfo>>;
+
+    /// `resume`: see `checkpoint.rs` — verify and continue a leftover
+    /// `.part` file from a previous cancelled/crashed attempt instead of
+    /// discarding it and starting over.
+    #[allow(clippy::too_many_arguments)]
+    async fn download_file_with_cancel(
+        &self,
+        repo_id: &str,
+        filename: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: Option<&str>,
+        force_revalidate: bool,
+        resume: bool,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String>;
+
+    /// `resume`: see [`Self::download_file_with_cancel`].
+    #[allow(clippy::too_many_arguments)]
+    async fn download_snapshot(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: &str,
+        allow_patterns: Option<Vec<String>>,
+        ignore_patterns: Option<Vec<String>>,
+        force_revalidate: bool,
+        resume: bool,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String>;
+
+    /// Upload a single local file, committing it in its backend's own atomic
+    /// unit of work. Backends with no upload path of their own (e.g. a
+    /// read-only object-store mirror) return an error.
+    #[allow(clippy::too_many_arguments)]
+    async fn upload_file(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_path: &str,
+        remote_path: &str,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String>;
+
+    /// Upload every file under `local_dir` (after pattern filtering) as a
+    /// single commit. See [`Self::upload_file`] for backends without an
+    /// upload path.
+    #[allow(clippy::too_many_arguments)]
+    async fn upload_snapshot(
+        &self,
+        repo_id: &str,
+        repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: &str,
+        allow_patterns: Option<Vec<String>>,
+        ignore_patterns: Option<Vec<String>>,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String>;
+}
+
+/// Apply the same `allow_patterns`/`ignore_patterns` glob filtering every
+/// backend's `download_snapshot` uses: a file is kept if its path matches
+/// at least one `allow_patterns` glob (when given) and matches none of
+/// `ignore_patterns`'s. A pattern prefixed with `!` (e.g. `!*.bin`) always
+/// means "exclude", regardless of which list it's passed in — so an
+/// exclusion accidentally (or deliberately) listed alongside `allow_patterns`
+/// still excludes rather than requiring a match against its own negation.
+pub(crate) fn filter_patterns(
+    files: Vec<HfFileInfo>,
+    allow_patterns: &Option<Vec<String>>,
+    ignore_patterns: &Option<Vec<String>>,
+) -> Vec<HfFileInfo> {
+    let (allow_includes, allow_excludes) = allow_patterns
+        .as_deref()
+        .map(split_excludes)
+        .unwrap_or_default();
+    let (ignore_includes, ignore_excludes) = ignore_patterns
+        .as_deref()
+        .map(split_excludes)
+        .unwrap_or_default();
+
+    let excludes: Vec<&str> = allow_excludes
+        .into_iter()
+        .chain(ignore_includes)
+        .chain(ignore_excludes)
+        .collect();
+
+    files
+        .into_iter()
+        .filter(|f| {
+            if !allow_includes.is_empty() && !allow_includes.iter().any(|p| glob_match(p, &f.path)) {
+                return false;
+            }
+            !excludes.iter().any(|p| glob_match(p, &f.path))
+        })
+        .collect()
+}
+
+/// Split a pattern list into plain ("include") globs and the globs of its
+/// `!`-prefixed ("exclude") entries, with the `!` stripped off.
+fn split_excludes(patterns: &[String]) -> (Vec<&str>, Vec<&str>) {
+    let mut includes = Vec::new();
+    let mut excludes = Vec::new();
+    for pattern in patterns {
+        match pattern.strip_prefix('!') {
+            Some(rest) => excludes.push(rest),
+            None => includes.push(pattern.as_str()),
+        }
+    }
+    (includes, excludes)
+}
+
+/// Match `path` against a shell-style glob `pattern` (`*` = any run of
+/// characters, `?` = exactly one), anchored at both ends. No dependency is
+/// pulled in for this since the grammar this needs is tiny.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    glob_match_bytes(pattern.as_bytes(), path.as_bytes())
+}
+
+fn glob_match_bytes(pattern: &[u8], path: &[u8]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob_match_bytes(&pattern[1..], path)
+                || (!path.is_empty() && glob_match_bytes(pattern, &path[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob_match_bytes(&pattern[1..], &path[1..]),
+        (Some(p), Some(c)) if p == c => glob_match_bytes(&pattern[1..], &path[1..]),
+        _ => false,
+    }
+}
+
+/// Bounds and pacing for `run_snapshot_download`'s adaptive concurrency
+/// controller: it starts at `min_in_flight`, grows the in-flight count while
+/// aggregate throughput is still climbing, and backs off toward
+/// `min_in_flight` once extra streams stop helping (or a download errors
+/// out) — converging on whatever concurrency maximizes end-to-end throughput
+/// for this snapshot instead of a single static guess. The controller
+/// samples throughput from the operation's `OperationProgress`; without one
+/// it does not run, and the in-flight count stays at `min_in_flight`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AdaptiveConcurrencyConfig {
+    pub(crate) min_in_flight: usize,
+    pub(crate) max_in_flight: usize,
+    pub(crate) sample_interval: Duration,
+}
+
+impl AdaptiveConcurrencyConfig {
+    /// Derive bounds from a single `max_concurrent` knob: ramp up from a
+    /// conservative quarter of the ceiling (at least one in flight) and cap
+    /// growth at `max_concurrent` itself, which doubles as the token-bucket
+    /// ceiling on in-flight permits.
+    pub(crate) fn from_max_concurrent(max_concurrent: usize) -> Self {
+        let max_in_flight = max_concurrent.max(1);
+        let min_in_flight = (max_in_flight / 4).max(1).min(max_in_flight);
+        Self {
+            min_in_flight,
+            max_in_flight,
+            sample_interval: Duration::from_millis(300),
+        }
+    }
+}
+
+/// Shared `download_snapshot` fan-out: filter `files` by pattern, then drive
+/// `download_one` over them with adaptive bounded concurrency, honoring
+/// cancellation and reporting progress — the same semaphore/cancellation/
+/// progress plumbing `HfAdapter::download_snapshot` used to inline, now
+/// reusable by every `ModelDownloader` backend.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_snapshot_download<D, Fut>(
+    files: Vec<HfFileInfo>,
+    local_dir: &str,
+    allow_patterns: &Option<Vec<String>>,
+    ignore_patterns: &Option<Vec<String>>,
+    concurrency: AdaptiveConcurrencyConfig,
+    cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+    progress: Option<OperationProgress>,
+    download_one: D,
+) -> Result<String>
+where
+    D: Fn(HfFileInfo, Option<Arc<dyn Fn() -> bool + Send + Sync>>, Option<OperationProgress>) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    if let Some(ref tracker) = progress {
+        tracker.set_phase(XetProgressPhase::Scanning, true);
+    }
+
+    let filtered_files = filter_patterns(files, allow_patterns, ignore_patterns);
+
+    let total_bytes: u64 = filtered_files.iter().map(|f| f.size).sum();
+    if let Some(ref tracker) = progress {
+        tracker.set_total_hint(filtered_files.len(), total_bytes);
+        tracker.set_phase(XetProgressPhase::Downloading, true);
+    }
+
+    fs::create_dir_all(local_dir).await?;
+
+    let file_count = filtered_files.len().max(1);
+    let max_in_flight = concurrency.max_in_flight.max(1).min(file_count);
+    let min_in_flight = concurrency.min_in_flight.max(1).min(max_in_flight);
+
+    let semaphore = Arc::new(Semaphore::new(min_in_flight));
+    let error_count = Arc::new(AtomicUsize::new(0));
+    let progress_shared = progress.as_ref().map(|p| p.clone_for_tasks());
+
+    let controller = (min_in_flight < max_in_flight && progress_shared.is_some()).then(|| {
+        tokio::spawn(adapt_concurrency(
+            semaphore.clone(),
+            error_count.clone(),
+            progress_shared.clone(),
+            min_in_flight,
+            max_in_flight,
+            concurrency.sample_interval,
+        ))
+    });
+
+    let download_futures = filtered_files.into_iter().map(|file| {
+        let semaphore = semaphore.clone();
+        let cancel_check = cancel_check.clone();
+        let progress = progress_shared.clone();
+        let error_count = error_count.clone();
+        let pending = download_one(file, cancel_check.clone(), progress);
+
+        async move {
+            let _permit = semaphore.acquire().await?;
+
+            if cancel_check.as_ref().map(|c| c()).unwrap_or(false) {
+                return Err(anyhow!("Download cancelled"));
+            }
+
+            let result = pending.await;
+            if result.is_err() {
+                error_count.fetch_add(1, Ordering::Relaxed);
+            }
+            result
+        }
+    });
+
+    let results: Vec<Result<String>> = stream::iter(download_futures)
+        .buffer_unordered(max_in_flight)
+        .collect()
+        .await;
+
+    if let Some(controller) = controller {
+        controller.abort();
+    }
+
+    for result in results {
+        result?;
+    }
+
+    if let Some(tracker) = progress {
+        tracker.finalize();
+    }
+
+    Ok(local_dir.to_string())
+}
+
+/// Background loop backing the adaptive scheduler: every `sample_interval`
+/// it samples aggregate completed bytes from `progress` and resizes
+/// `semaphore` by at most one permit — growing via `add_permits` while
+/// throughput is still climbing and no new errors landed since the last
+/// tick, shrinking by acquiring and `forget`-ing a permit otherwise (the
+/// standard way to permanently remove a permit from a `Semaphore` without
+/// recreating it). `semaphore`'s capacity never leaves
+/// `[min_in_flight, max_in_flight]`.
+async fn adapt_concurrency(
+    semaphore: Arc<Semaphore>,
+    error_count: Arc<AtomicUsize>,
+    progress: Option<OperationProgress>,
+    min_in_flight: usize,
+    max_in_flight: usize,
+    sample_interval: Duration,
+) {
+    let Some(progress) = progress else {
+        return;
+    };
+
+    let mut current = min_in_flight;
+    let mut last_bytes = progress.snapshot_completed_bytes();
+    let mut last_throughput = 0.0_f64;
+    let mut last_errors = error_count.load(Ordering::Relaxed);
+
+    loop {
+        sleep(sample_interval).await;
+
+        let bytes = progress.snapshot_completed_bytes();
+        let throughput = bytes.saturating_sub(last_bytes) as f64 / sample_interval.as_secs_f64();
+        let errors = error_count.load(Ordering::Relaxed);
+
+        if errors > last_errors {
+            current = shrink_by_one(&semaphore, current, min_in_flight);
+        } else if throughput > last_throughput && current < max_in_flight {
+            semaphore.add_permits(1);
+            current += 1;
+        } else if current > min_in_flight {
+            current = shrink_by_one(&semaphore, current, min_in_flight);
+        }
+
+        last_bytes = bytes;
+        last_throughput = throughput;
+        last_errors = errors;
+    }
+}
+
+/// Permanently remove one permit from `semaphore`, unless it's already at
+/// `floor`. A no-op if every permit is currently checked out; the next
+/// sampling tick tries again.
+fn shrink_by_one(semaphore: &Arc<Semaphore>, current: usize, floor: usize) -> usize {
+    if current <= floor {
+        return current;
+    }
+    match semaphore.try_acquire() {
+        Ok(permit) => {
+            permit.forget();
+            current - 1
+        }
+        Err(_) => current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str) -> HfFileInfo {
+        HfFileInfo {
+            path: path.to_string(),
+            hash: String::new(),
+            size: 0,
+            xet_hash: None,
+        }
+    }
+
+    #[test]
+    fn allow_pattern_is_a_real_glob_not_a_substring() {
+        let files = vec![file("model.safetensors"), file("model.bin"), file("README.md")];
+        let kept = filter_patterns(files, &Some(vec!["*.safetensors".to_string()]), &None);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].path, "model.safetensors");
+    }
+
+    #[test]
+    fn bang_prefixed_pattern_excludes_even_inside_allow_patterns() {
+        let files = vec![file("model.safetensors"), file("model.bin")];
+        let kept = filter_patterns(
+            files,
+            &Some(vec!["*.safetensors".to_string(), "!*.bin".to_string()]),
+            &None,
+        );
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].path, "model.safetensors");
+    }
+}
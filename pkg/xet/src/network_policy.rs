@@ -0,0 +1,133 @@
+// Host allowlist/denylist enforcement for outbound requests.
+//
+// The CAS URL and `xet-auth` refresh route are server-controlled redirects
+// returned in HF API response headers, so a compromised or misconfigured
+// API response could otherwise point the client at an arbitrary endpoint
+// with the user's token attached. `NetworkPolicy` is checked before every
+// outbound request so that can't happen silently.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Error returned when a request targets a host the active `NetworkPolicy`
+/// does not permit.
+#[derive(Debug)]
+pub(crate) struct PermissionDeniedError {
+    pub host: String,
+}
+
+impl std::fmt::Display for PermissionDeniedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "network policy denied request to host: {}", self.host)
+    }
+}
+
+impl std::error::Error for PermissionDeniedError {}
+
+/// Allow/deny list of host patterns (e.g. `*.hf.co`) constraining which
+/// network destinations the downloader may contact.
+///
+/// A host that matches the deny list is always rejected. Otherwise, if an
+/// allow list is configured, the host must match one of its patterns —
+/// unless a `prompt_hook` is set, in which case an unmatched host is
+/// offered to the hook once and the decision is cached for the life of the
+/// policy.
+pub struct NetworkPolicy {
+    allow: Vec<String>,
+    deny: Vec<String>,
+    prompt_hook: Option<Arc<dyn Fn(&str) -> bool + Send + Sync>>,
+    decision_cache: Mutex<HashMap<String, bool>>,
+}
+
+impl NetworkPolicy {
+    pub fn new(allow: Vec<String>, deny: Vec<String>) -> Self {
+        Self {
+            allow,
+            deny,
+            prompt_hook: None,
+            decision_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Attach a "prompt once, cache the decision" hook for hosts that are
+    /// neither explicitly allowed nor denied.
+    pub fn with_prompt_hook(mut self, hook: Arc<dyn Fn(&str) -> bool + Send + Sync>) -> Self {
+        self.prompt_hook = Some(hook);
+        self
+    }
+
+    /// Check whether `host` is permitted, returning a
+    /// [`PermissionDeniedError`] (via `anyhow`) naming the blocked host if not.
+    pub fn check(&self, host: &str) -> anyhow::Result<()> {
+        if matches_any(&self.deny, host) {
+            return Err(anyhow::Error::new(PermissionDeniedError {
+                host: host.to_string(),
+            }));
+        }
+
+        if self.allow.is_empty() || matches_any(&self.allow, host) {
+            return Ok(());
+        }
+
+        if let Some(ref hook) = self.prompt_hook {
+            let mut cache = self.decision_cache.lock().expect("policy cache poisoned");
+            let decision = *cache.entry(host.to_string()).or_insert_with(|| hook(host));
+            if decision {
+                return Ok(());
+            }
+        }
+
+        Err(anyhow::Error::new(PermissionDeniedError {
+            host: host.to_string(),
+        }))
+    }
+
+    /// Convenience wrapper that extracts the host from `url` before checking.
+    pub fn check_url(&self, url: &str) -> anyhow::Result<()> {
+        let host = crate::credentials::host_of(url)
+            .ok_or_else(|| anyhow::anyhow!("could not determine host for URL: {}", url))?;
+        self.check(&host)
+    }
+}
+
+fn matches_any(patterns: &[String], host: &str) -> bool {
+    patterns.iter().any(|pattern| matches_pattern(pattern, host))
+}
+
+fn matches_pattern(pattern: &str, host: &str) -> bool {
+    if let Some(suffix) = pattern.strip_prefix("*.") {
+        return host == suffix || host.ends_with(&format!(".{}", suffix));
+    }
+    pattern.eq_ignore_ascii_case(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_pattern_matches_subdomains_and_apex() {
+        assert!(matches_pattern("*.hf.co", "cas-server.hf.co"));
+        assert!(matches_pattern("*.hf.co", "hf.co"));
+        assert!(!matches_pattern("*.hf.co", "hf.co.evil.com"));
+    }
+
+    #[test]
+    fn deny_list_wins_over_allow_list() {
+        let policy = NetworkPolicy::new(vec!["*.hf.co".into()], vec!["evil.hf.co".into()]);
+        assert!(policy.check("cas.hf.co").is_ok());
+        assert!(policy.check("evil.hf.co").is_err());
+    }
+
+    #[test]
+    fn unmatched_host_denied_without_allow_list_entry() {
+        let policy = NetworkPolicy::new(vec!["huggingface.co".into()], vec![]);
+        assert!(policy.check("attacker.example.com").is_err());
+    }
+
+    #[test]
+    fn empty_allow_list_permits_everything_not_denied() {
+        let policy = NetworkPolicy::new(vec![], vec!["blocked.example.com".into()]);
+        assert!(policy.check("anything.example.com").is_ok());
+        assert!(policy.check("blocked.example.com").is_err());
+    }
+}
@@ -0,0 +1,602 @@
+// `ModelDownloader` backend for S3-compatible object stores (AWS S3, MinIO,
+// and similar), so a model mirror can live in a bucket instead of behind the
+// HuggingFace tree/resolve API. Lists via `ListObjectsV2` and downloads via
+// SigV4-signed GETs; both request kinds go through the same signer.
+use crate::hf_adapter::{determine_destination, is_cancelled, HfFileInfo};
+use crate::http_config::HttpClientConfig;
+use crate::io_writer::{DownloadWriter, IoWriterConfig};
+use crate::model_downloader::{self, AdaptiveConcurrencyConfig, ModelDownloader};
+use crate::progress::OperationProgress;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::fs;
+
+/// Credentials and addressing for an S3-compatible bucket. `endpoint`
+/// defaults to AWS's virtual-hosted endpoint for `region`; set it to target
+/// a self-hosted or non-AWS S3-compatible store (MinIO, Ceph RGW, ...).
+#[derive(Debug, Clone)]
+pub struct ObjectStoreConfig {
+    pub bucket: String,
+    /// Key prefix every repo lives under within `bucket` (e.g. the path
+    /// component of an `s3://bucket/models` URI), prepended to
+    /// `{repo_id}/{revision}/` when building object keys. `None` for a
+    /// bucket dedicated entirely to model repos.
+    pub base_prefix: Option<String>,
+    pub region: String,
+    pub endpoint: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl ObjectStoreConfig {
+    /// Parse an `s3://bucket[/prefix]` URI into a bucket-scoped config,
+    /// sourcing credentials and region from the environment the same way
+    /// the AWS CLI does (`AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`,
+    /// `AWS_SESSION_TOKEN`, `AWS_REGION`/`AWS_DEFAULT_REGION`), plus
+    /// `AWS_ENDPOINT_URL` for S3-compatible stores (MinIO, Ceph RGW, ...).
+    /// Returns `None` for any other scheme so the caller falls back to the
+    /// HuggingFace backend.
+    pub fn from_uri(uri: &str) -> Result<Option<Self>> {
+        let Some(rest) = uri.strip_prefix("s3://") else {
+            return Ok(None);
+        };
+
+        let mut parts = rest.splitn(2, '/');
+        let bucket = parts
+            .next()
+            .filter(|b| !b.is_empty())
+            .ok_or_else(|| anyhow!("invalid s3:// URI, missing bucket: {}", uri))?
+            .to_string();
+        let base_prefix = parts.next().filter(|p| !p.is_empty()).map(String::from);
+
+        let region = std::env::var("AWS_REGION")
+            .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+            .unwrap_or_else(|_| "us-east-1".to_string());
+        let access_key_id = std::env::var("AWS_ACCESS_KEY_ID")
+            .context("s3:// endpoint requires the AWS_ACCESS_KEY_ID environment variable")?;
+        let secret_access_key = std::env::var("AWS_SECRET_ACCESS_KEY")
+            .context("s3:// endpoint requires the AWS_SECRET_ACCESS_KEY environment variable")?;
+        let session_token = std::env::var("AWS_SESSION_TOKEN").ok();
+        let endpoint = std::env::var("AWS_ENDPOINT_URL").ok();
+
+        Ok(Some(Self {
+            bucket,
+            base_prefix,
+            region,
+            endpoint,
+            access_key_id,
+            secret_access_key,
+            session_token,
+        }))
+    }
+}
+
+/// `ModelDownloader` over an S3-compatible bucket. A repo's files live under
+/// the key prefix `{repo_id}/{revision}/`, mirroring the directory layout
+/// `HfAdapter` writes into its local cache.
+#[derive(Clone)]
+pub(crate) struct ObjectStoreDownloader {
+    config: ObjectStoreConfig,
+    cache_dir: Option<std::path::PathBuf>,
+    max_concurrent: usize,
+    client: reqwest::Client,
+    io_writer_config: IoWriterConfig,
+}
+
+impl ObjectStoreDownloader {
+    pub(crate) fn new(
+        config: ObjectStoreConfig,
+        cache_dir: Option<String>,
+        max_concurrent: usize,
+        http_config: HttpClientConfig,
+        force_disable_io_uring: bool,
+    ) -> Result<Self> {
+        let client = http_config.apply(reqwest::Client::builder())?.build()?;
+        Ok(Self {
+            config,
+            cache_dir: cache_dir.map(std::path::PathBuf::from),
+            max_concurrent: max_concurrent.max(1),
+            client,
+            io_writer_config: IoWriterConfig {
+                force_disable_io_uring,
+            },
+        })
+    }
+
+    /// Virtual-hosted style (`bucket.s3.region.amazonaws.com`) against AWS
+    /// itself; path style (just the configured endpoint's host, with the
+    /// bucket folded into the object path) against a custom `endpoint` —
+    /// most self-hosted S3-compatible stores don't do virtual-hosted buckets.
+    fn host(&self) -> String {
+        match &self.config.endpoint {
+            Some(endpoint) => endpoint
+                .trim_start_matches("https://")
+                .trim_start_matches("http://")
+                .to_string(),
+            None => format!("{}.s3.{}.amazonaws.com", self.config.bucket, self.config.region),
+        }
+    }
+
+    fn base_url(&self) -> String {
+        format!("https://{}", self.host())
+    }
+
+    /// Canonical URI for an object GET: `/{key}` virtual-hosted, or
+    /// `/{bucket}/{key}` path-style.
+    fn object_path(&self, key: &str) -> String {
+        format!("{}/{}", self.bucket_root(), percent_encode_path(key))
+    }
+
+    /// Canonical URI for a bucket-level request (`ListObjectsV2`): `/`
+    /// virtual-hosted, or `/{bucket}` path-style.
+    fn bucket_root(&self) -> String {
+        if self.config.endpoint.is_some() {
+            format!("/{}", percent_encode_component(&self.config.bucket))
+        } else {
+            String::new()
+        }
+    }
+
+    /// Canonical URI for the `ListObjectsV2` request itself (`bucket_root`
+    /// is empty virtual-hosted, but the URI still needs a leading `/`).
+    fn list_uri(&self) -> String {
+        let root = self.bucket_root();
+        if root.is_empty() {
+            "/".to_string()
+        } else {
+            root
+        }
+    }
+
+    fn key_prefix(&self, repo_id: &str, revision: &str) -> String {
+        let repo_prefix = format!("{}/{}/", repo_id.trim_matches('/'), revision);
+        match &self.config.base_prefix {
+            Some(base) => format!("{}/{}", base.trim_matches('/'), repo_prefix),
+            None => repo_prefix,
+        }
+    }
+
+    /// Sign and send a GET to `canonical_uri` (already percent-encoded, `/`
+    /// leading) with `query` as the raw query string (may be empty).
+    async fn signed_get(&self, canonical_uri: &str, query: &str) -> Result<reqwest::Response> {
+        let host = self.host();
+        let now = SystemTime::now();
+        let headers = sigv4_sign(
+            "GET",
+            &host,
+            canonical_uri,
+            query,
+            &self.config.region,
+            "s3",
+            &self.config.access_key_id,
+            &self.config.secret_access_key,
+            self.config.session_token.as_deref(),
+            now,
+        );
+
+        let url = if query.is_empty() {
+            format!("{}{}", self.base_url(), canonical_uri)
+        } else {
+            format!("{}{}?{}", self.base_url(), canonical_uri, query)
+        };
+
+        let mut request = self.client.get(&url);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "object store request to {} failed: HTTP {}",
+                url,
+                response.status()
+            ));
+        }
+        Ok(response)
+    }
+
+    async fn download_one(
+        &self,
+        repo_id: &str,
+        revision: &str,
+        local_dir: Option<&str>,
+        file_info: &HfFileInfo,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        if is_cancelled(&cancel_check) {
+            return Err(anyhow!("Download cancelled"));
+        }
+
+        let destination = determine_destination(
+            local_dir,
+            self.cache_dir.as_deref(),
+            repo_id,
+            revision,
+            &file_info.path,
+        );
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        if destination.exists() {
+            if let Ok(metadata) = fs::metadata(&destination).await {
+                if metadata.len() == file_info.size {
+                    if let Some(ref tracker) = progress {
+                        tracker.ensure_file_entry(&file_info.path, file_info.size);
+                        tracker.update_file_absolute(&file_info.path, file_info.size, file_info.size, true);
+                    }
+                    return Ok(destination.to_string_lossy().to_string());
+                }
+            }
+        }
+
+        let key = format!("{}{}", self.key_prefix(repo_id, revision), file_info.path);
+        let canonical_uri = self.object_path(&key);
+        let response = self.signed_get(&canonical_uri, "").await?;
+
+        if let Some(ref tracker) = progress {
+            tracker.ensure_file_entry(&file_info.path, file_info.size);
+        }
+
+        let mut stream = response.bytes_stream();
+        let mut file = DownloadWriter::create(&destination, self.io_writer_config).await?;
+        let mut downloaded: u64 = 0;
+
+        while let Some(chunk) = stream.next().await {
+            if is_cancelled(&cancel_check) {
+                return Err(anyhow!("Download cancelled"));
+            }
+            let chunk = chunk.context("reading object store response body")?;
+            file.write_all(&chunk).await?;
+            downloaded += chunk.len() as u64;
+            if let Some(ref tracker) = progress {
+                tracker.update_file_absolute(&file_info.path, downloaded, file_info.size, false);
+            }
+        }
+        file.flush().await?;
+
+        if let Some(ref tracker) = progress {
+            tracker.update_file_absolute(&file_info.path, downloaded, file_info.size, true);
+        }
+
+        Ok(destination.to_string_lossy().to_string())
+    }
+}
+
+#[async_trait]
+impl ModelDownloader for ObjectStoreDownloader {
+    async fn list_files(&self, repo_id: &str, revision: Option<&str>) -> Result<Vec<HfFileInfo>> {
+        let revision = revision.unwrap_or("main");
+        let prefix = self.key_prefix(repo_id, revision);
+        let query = format!(
+            "list-type=2&prefix={}",
+            percent_encode_query(&prefix)
+        );
+
+        let response = self.signed_get(&self.list_uri(), &query).await?;
+        let body = response.text().await?;
+        parse_list_objects_v2(&body, &prefix)
+    }
+
+    async fn download_file_with_cancel(
+        &self,
+        repo_id: &str,
+        filename: &str,
+        _repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: Option<&str>,
+        _force_revalidate: bool,
+        // Object store GETs always fetch the whole object fresh; there's no
+        // partial-file resume path to opt into here.
+        _resume: bool,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        let revision = revision.unwrap_or("main");
+        let files = self.list_files(repo_id, Some(revision)).await?;
+        let file_info = files
+            .into_iter()
+            .find(|f| f.path == filename)
+            .ok_or_else(|| anyhow!("File {} not found under {}/{}", filename, repo_id, revision))?;
+
+        self.download_one(repo_id, revision, local_dir, &file_info, cancel_check, progress)
+            .await
+    }
+
+    async fn download_snapshot(
+        &self,
+        repo_id: &str,
+        _repo_type: Option<&str>,
+        revision: Option<&str>,
+        local_dir: &str,
+        allow_patterns: Option<Vec<String>>,
+        ignore_patterns: Option<Vec<String>>,
+        // There's no revalidation metadata here: `download_one` keeps an
+        // existing local file whose size matches the listing without a GET,
+        // whether or not this is set.
+        _force_revalidate: bool,
+        // See `download_file_with_cancel`'s `_resume`.
+        _resume: bool,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        let revision = revision.unwrap_or("main").to_string();
+        let files = self.list_files(repo_id, Some(&revision)).await?;
+
+        let this = self.clone();
+        let repo_id = repo_id.to_string();
+
+        model_downloader::run_snapshot_download(
+            files,
+            local_dir,
+            &allow_patterns,
+            &ignore_patterns,
+            AdaptiveConcurrencyConfig::from_max_concurrent(self.max_concurrent),
+            cancel_check,
+            progress,
+            move |file, cancel_check, progress| {
+                let this = this.clone();
+                let repo_id = repo_id.clone();
+                let revision = revision.clone();
+                let local_dir = local_dir.to_string();
+                async move {
+                    this.download_one(&repo_id, &revision, Some(&local_dir), &file, cancel_check, progress)
+                        .await
+                }
+            },
+        )
+        .await
+    }
+
+    async fn upload_file(
+        &self,
+        _repo_id: &str,
+        _repo_type: Option<&str>,
+        _revision: Option<&str>,
+        _local_path: &str,
+        _remote_path: &str,
+        _cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        _progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        Err(anyhow!("uploads are not supported for an object-store endpoint"))
+    }
+
+    async fn upload_snapshot(
+        &self,
+        _repo_id: &str,
+        _repo_type: Option<&str>,
+        _revision: Option<&str>,
+        _local_dir: &str,
+        _allow_patterns: Option<Vec<String>>,
+        _ignore_patterns: Option<Vec<String>>,
+        _cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        _progress: Option<OperationProgress>,
+    ) -> Result<String> {
+        Err(anyhow!("uploads are not supported for an object-store endpoint"))
+    }
+}
+
+/// Minimal `ListObjectsV2` XML response parser: extracts `Key`/`Size` out of
+/// each `<Contents>` entry, stripping the repo/revision prefix back off so
+/// callers see paths relative to the repo root (matching `HfFileInfo::path`
+/// from the HF backend). No XML parsing crate is pulled in for three fields.
+fn parse_list_objects_v2(body: &str, prefix: &str) -> Result<Vec<HfFileInfo>> {
+    let mut files = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("<Contents>") {
+        let after_start = &rest[start + "<Contents>".len()..];
+        let end = after_start
+            .find("</Contents>")
+            .ok_or_else(|| anyhow!("malformed ListObjectsV2 response: unterminated <Contents>"))?;
+        let entry = &after_start[..end];
+
+        let key = extract_xml_tag(entry, "Key")
+            .ok_or_else(|| anyhow!("ListObjectsV2 entry missing <Key>"))?;
+        let size: u64 = extract_xml_tag(entry, "Size")
+            .ok_or_else(|| anyhow!("ListObjectsV2 entry missing <Size>"))?
+            .parse()
+            .context("parsing ListObjectsV2 <Size>")?;
+        let etag = extract_xml_tag(entry, "ETag").unwrap_or_default();
+
+        let path = key.strip_prefix(prefix).unwrap_or(&key).to_string();
+        if !path.is_empty() {
+            files.push(HfFileInfo {
+                path,
+                hash: etag.trim_matches('"').to_string(),
+                size,
+                xet_hash: None,
+            });
+        }
+
+        rest = &after_start[end + "</Contents>".len()..];
+    }
+    Ok(files)
+}
+
+fn extract_xml_tag(body: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = body.find(&open)? + open.len();
+    let end = body[start..].find(&close)? + start;
+    Some(xml_unescape(&body[start..end]))
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+}
+
+fn percent_encode_path(path: &str) -> String {
+    path.split('/')
+        .map(percent_encode_component)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn percent_encode_query(value: &str) -> String {
+    percent_encode_component(value)
+}
+
+fn percent_encode_component(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Sign a request per AWS Signature Version 4 and return the headers to
+/// attach (`Host`, `x-amz-date`, `x-amz-content-sha256`, `x-amz-security-token`
+/// when a session token is set, and `Authorization`). Only covers
+/// unsigned-payload GETs, which is all this backend issues.
+#[allow(clippy::too_many_arguments)]
+fn sigv4_sign(
+    method: &str,
+    host: &str,
+    canonical_uri: &str,
+    canonical_query: &str,
+    region: &str,
+    service: &str,
+    access_key: &str,
+    secret_key: &str,
+    session_token: Option<&str>,
+    now: SystemTime,
+) -> Vec<(String, String)> {
+    let (amz_date, date_stamp) = format_amz_date(now);
+    let payload_hash = sha256_hex(b"");
+
+    let mut signed_header_names = vec!["host", "x-amz-content-sha256", "x-amz-date"];
+    if session_token.is_some() {
+        signed_header_names.push("x-amz-security-token");
+    }
+    signed_header_names.sort_unstable();
+    let signed_headers = signed_header_names.join(";");
+
+    let mut canonical_headers = format!(
+        "host:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n",
+        host, payload_hash, amz_date
+    );
+    if let Some(token) = session_token {
+        canonical_headers = merge_canonical_header(canonical_headers, "x-amz-security-token", token);
+    }
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method, canonical_uri, canonical_query, canonical_headers, signed_headers, payload_hash
+    );
+
+    let credential_scope = format!("{}/{}/{}/aws4_request", date_stamp, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        credential_scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let k_date = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date_stamp.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    let k_signing = hmac_sha256(&k_service, b"aws4_request");
+    let signature = hex_encode(&hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key, credential_scope, signed_headers, signature
+    );
+
+    let mut headers = vec![
+        ("host".to_string(), host.to_string()),
+        ("x-amz-date".to_string(), amz_date),
+        ("x-amz-content-sha256".to_string(), payload_hash),
+        ("Authorization".to_string(), authorization),
+    ];
+    if let Some(token) = session_token {
+        headers.push(("x-amz-security-token".to_string(), token.to_string()));
+    }
+    headers
+}
+
+fn merge_canonical_header(mut canonical_headers: String, name: &str, value: &str) -> String {
+    canonical_headers.push_str(name);
+    canonical_headers.push(':');
+    canonical_headers.push_str(value);
+    canonical_headers.push('\n');
+    canonical_headers
+}
+
+/// Format `now` as the `x-amz-date` (`YYYYMMDDTHHMMSSZ`) and credential-scope
+/// date stamp (`YYYYMMDD`) SigV4 needs, without pulling in a datetime crate.
+fn format_amz_date(now: SystemTime) -> (String, String) {
+    let total_secs = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs() as i64;
+    let days = total_secs.div_euclid(86_400);
+    let secs_of_day = total_secs.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    let hour = secs_of_day / 3600;
+    let minute = (secs_of_day % 3600) / 60;
+    let second = secs_of_day % 60;
+
+    let amz_date = format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year, month, day, hour, minute, second
+    );
+    let date_stamp = format!("{:04}{:02}{:02}", year, month, day);
+    (amz_date, date_stamp)
+}
+
+/// Inverse of `hf_adapter`'s `days_from_civil`: the proleptic-Gregorian
+/// `(year, month, day)` for a day count since the Unix epoch, via Howard
+/// Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hex_encode(&hasher.finalize())
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        let _ = write!(out, "{:02x}", b);
+    }
+    out
+}
@@ -12,6 +12,13 @@ pub enum XetProgressPhase {
     Scanning = 0,
     Downloading = 1,
     Finalizing = 2,
+    /// A transfer hit a retryable error and is backing off before another
+    /// attempt; see the retry subsystem in `retry.rs`.
+    Retrying = 3,
+    /// An upload is scanning and content-defined-chunking/hashing local
+    /// bytes before it knows which chunks are even worth uploading; see
+    /// `chunker.rs`.
+    Hashing = 4,
 }
 
 #[repr(C)]
@@ -24,6 +31,13 @@ pub struct XetProgressUpdate {
     pub current_file: *const c_char,
     pub current_file_completed_bytes: u64,
     pub current_file_total_bytes: u64,
+    /// Exponentially-weighted moving average of transfer speed, smoothed
+    /// across emissions so throttled/irregular callback timing doesn't read
+    /// out as a noisy rate. See [`OperationProgressState`]'s rate tracking.
+    pub instantaneous_bytes_per_sec: u64,
+    /// `(total_bytes - completed_bytes) / instantaneous_bytes_per_sec`,
+    /// saturating to `u64::MAX` while the rate isn't known yet.
+    pub eta_seconds: u64,
 }
 
 pub type XetProgressCallback = unsafe extern "C" fn(*const XetProgressUpdate, *mut c_void);
@@ -100,8 +114,25 @@ struct OperationProgressState {
     files: HashMap<String, FileProgress>,
     total_files_hint: Option<usize>,
     last_emit: Option<Instant>,
+    /// `(Instant, completed_bytes)` of the previous rate sample, i.e. the
+    /// previous non-throttled `emit`. `None` until the first sample.
+    last_rate_sample: Option<(Instant, u64)>,
+    /// EWMA of bytes/sec, seeded from the first instantaneous sample rather
+    /// than blended in (which would otherwise bias the first few readings
+    /// toward zero). `None` until seeded.
+    smoothed_bytes_per_sec: Option<f64>,
 }
 
+/// Weight given to the newest instantaneous sample when blending into the
+/// smoothed rate; the rest comes from the previous smoothed value.
+const RATE_EWMA_ALPHA: f64 = 0.3;
+
+/// Per-emission decay applied to the smoothed rate once the operation enters
+/// [`XetProgressPhase::Finalizing`], so a tiny/zero elapsed time across that
+/// transition (the phase flip and the last byte both land in the same forced
+/// emit) doesn't read out as a rate spike or an abrupt drop to zero.
+const FINALIZING_RATE_DECAY: f64 = 0.5;
+
 #[derive(Default)]
 struct FileProgress {
     total_bytes: u64,
@@ -130,6 +161,8 @@ impl OperationProgress {
                 files: HashMap::new(),
                 total_files_hint: None,
                 last_emit: None,
+                last_rate_sample: None,
+                smoothed_bytes_per_sec: None,
             })),
         }
     }
@@ -196,6 +229,18 @@ impl OperationProgress {
         );
     }
 
+    /// Zero out a file's completed-bytes accounting (and the matching
+    /// aggregate total), for a retry that restarts the file from scratch
+    /// because the server didn't honor a range-resume request.
+    pub fn reset_file_progress(&self, name: &str) {
+        let mut state = self.inner.lock().expect("progress mutex poisoned");
+        if let Some(entry) = state.files.get_mut(name) {
+            let completed = entry.completed_bytes;
+            entry.completed_bytes = 0;
+            state.completed_bytes = state.completed_bytes.saturating_sub(completed);
+        }
+    }
+
     pub fn apply_tracking_update(&self, update: &TrackingProgressUpdate) {
         self.set_total_bytes(update.total_transfer_bytes);
         self.set_completed_bytes(update.total_transfer_bytes_completed);
@@ -214,6 +259,13 @@ impl OperationProgress {
         self.set_phase(XetProgressPhase::Finalizing, true);
     }
 
+    /// Current aggregate completed-bytes total, for callers (e.g. an
+    /// adaptive concurrency controller) that need to sample throughput
+    /// without waiting for a throttled callback emission.
+    pub fn snapshot_completed_bytes(&self) -> u64 {
+        self.inner.lock().expect("progress mutex poisoned").completed_bytes
+    }
+
     pub fn force_emit(&self) {
         self.emit(EmitFileInfo::None, true);
     }
@@ -239,6 +291,37 @@ impl OperationProgress {
 
         let now = Instant::now();
 
+        if state.phase == XetProgressPhase::Finalizing {
+            state.smoothed_bytes_per_sec = state
+                .smoothed_bytes_per_sec
+                .map(|rate| rate * FINALIZING_RATE_DECAY);
+        } else if let Some((last_time, last_bytes)) = state.last_rate_sample {
+            let dt = now.duration_since(last_time).as_secs_f64();
+            if dt > 0.0 {
+                let instantaneous = state.completed_bytes.saturating_sub(last_bytes) as f64 / dt;
+                state.smoothed_bytes_per_sec = Some(match state.smoothed_bytes_per_sec {
+                    Some(prev) => RATE_EWMA_ALPHA * instantaneous + (1.0 - RATE_EWMA_ALPHA) * prev,
+                    None => instantaneous,
+                });
+            }
+        }
+        state.last_rate_sample = Some((now, state.completed_bytes));
+
+        let instantaneous_bytes_per_sec = state
+            .smoothed_bytes_per_sec
+            .map(|rate| rate.max(0.0).min(u64::MAX as f64) as u64)
+            .unwrap_or(0);
+
+        let remaining_bytes = state.total_bytes.saturating_sub(state.completed_bytes);
+        let eta_seconds = if remaining_bytes == 0 {
+            0
+        } else if instantaneous_bytes_per_sec == 0 {
+            u64::MAX
+        } else {
+            (remaining_bytes as f64 / instantaneous_bytes_per_sec as f64)
+                .min(u64::MAX as f64) as u64
+        };
+
         let total_files_hint = state.total_files_hint.unwrap_or_else(|| state.files.len());
         let total_files = total_files_hint.min(u32::MAX as usize) as u32;
         let completed_files = state
@@ -276,6 +359,8 @@ impl OperationProgress {
             current_file: file_name_ptr,
             current_file_completed_bytes: file_completed,
             current_file_total_bytes: file_total,
+            instantaneous_bytes_per_sec,
+            eta_seconds,
         };
 
         state.last_emit = Some(now);
@@ -0,0 +1,173 @@
+// Generic retryable-operation helper modeled on cargo's
+// `util::network::retry` `Retry`/`RetryResult` pattern: callers classify each
+// attempt's outcome, and this module owns the sleep/backoff/jitter/
+// cancellation-check loop so call sites don't each reimplement it.
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::time::sleep;
+
+/// Backoff bounds for [`retry`], configurable per client via `XetConfig`'s
+/// `retry_max_attempts`/`retry_base_delay_ms`/`retry_max_delay_ms`.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+/// The outcome of a single attempt passed to [`retry`].
+pub(crate) enum RetryResult<T> {
+    /// The attempt succeeded.
+    Success(T),
+    /// The attempt failed permanently; stop without retrying.
+    Err(anyhow::Error),
+    /// The attempt failed transiently; sleep and try again if attempts remain.
+    Retry(anyhow::Error),
+}
+
+/// Run `attempt` up to `config.max_attempts` times total, sleeping
+/// `base_delay * 2^n` (capped at `max_delay`, with full jitter) between a
+/// `RetryResult::Retry` outcome and the next attempt. `on_retry` is called
+/// just before each sleep (e.g. to flip the progress phase to `Retrying`);
+/// `cancel_check` is polled throughout the sleep so a cancellation aborts the
+/// wait rather than completing it.
+pub(crate) async fn retry<T, F, Fut>(
+    config: &RetryConfig,
+    cancel_check: &Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+    on_retry: impl Fn(u32, Duration),
+    mut attempt: F,
+) -> Result<T>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = RetryResult<T>>,
+{
+    let mut attempt_number = 0u32;
+    loop {
+        if is_cancelled(cancel_check) {
+            return Err(anyhow::anyhow!("operation cancelled"));
+        }
+
+        match attempt(attempt_number).await {
+            RetryResult::Success(value) => return Ok(value),
+            RetryResult::Err(err) => return Err(err),
+            RetryResult::Retry(err) => {
+                attempt_number += 1;
+                if attempt_number >= config.max_attempts {
+                    return Err(err);
+                }
+
+                let delay = backoff_with_jitter(config, attempt_number);
+                on_retry(attempt_number, delay);
+
+                if !sleep_cancellable(delay, cancel_check).await {
+                    return Err(anyhow::anyhow!("operation cancelled during retry backoff"));
+                }
+            }
+        }
+    }
+}
+
+/// Sleep for `delay`, polling `cancel_check` in short slices so a
+/// cancellation aborts the wait instead of completing it. Returns `false` if
+/// cancelled partway through.
+async fn sleep_cancellable(
+    delay: Duration,
+    cancel_check: &Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+) -> bool {
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+    let mut remaining = delay;
+    while remaining > Duration::ZERO {
+        if is_cancelled(cancel_check) {
+            return false;
+        }
+        let step = remaining.min(POLL_INTERVAL);
+        sleep(step).await;
+        remaining = remaining.saturating_sub(step);
+    }
+    true
+}
+
+fn is_cancelled(cancel_check: &Option<Arc<dyn Fn() -> bool + Send + Sync>>) -> bool {
+    cancel_check.as_ref().map(|cancel| cancel()).unwrap_or(false)
+}
+
+/// Exponential backoff with full jitter, capped at `config.max_delay`.
+fn backoff_with_jitter(config: &RetryConfig, attempt: u32) -> Duration {
+    let exp = config
+        .base_delay
+        .as_millis()
+        .saturating_mul(1u128 << attempt.min(20));
+    let capped = exp.min(config.max_delay.as_millis());
+    let jittered = rand_below(capped.max(1) as u64) as u128;
+    Duration::from_millis(jittered.min(config.max_delay.as_millis()) as u64)
+}
+
+/// Tiny dependency-free `[0, bound)` PRNG seeded from the system clock;
+/// sufficient for decorrelating retries, not for anything security-sensitive.
+fn rand_below(bound: u64) -> u64 {
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    seed.wrapping_mul(6364136223846793005).wrapping_add(1) % bound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::block_on;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn succeeds_without_retrying_on_first_try() {
+        let config = RetryConfig::default();
+        let calls = AtomicU32::new(0);
+        let result: Result<u32> = block_on(retry(&config, &None, |_, _| {}, |_| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            async { RetryResult::Success(42) }
+        }));
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn retries_transient_failures_up_to_max_attempts() {
+        let config = RetryConfig {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        };
+        let calls = AtomicU32::new(0);
+        let result: Result<u32> = block_on(retry(&config, &None, |_, _| {}, |_| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            async { RetryResult::Retry(anyhow::anyhow!("transient")) }
+        }));
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn stops_immediately_on_permanent_failure() {
+        let config = RetryConfig::default();
+        let calls = AtomicU32::new(0);
+        let result: Result<u32> = block_on(retry(&config, &None, |_, _| {}, |_| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            async { RetryResult::Err(anyhow::anyhow!("permanent")) }
+        }));
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
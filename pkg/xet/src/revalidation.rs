@@ -0,0 +1,169 @@
+// Sidecar revalidation metadata for conditional HTTP re-downloads.
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Revalidation record persisted alongside a downloaded file.
+///
+/// Lets a later download skip the transfer entirely via a conditional
+/// request (`If-None-Match`/`If-Modified-Since`) when the server reports
+/// `304 Not Modified`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedFileMetadata {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub xet_hash: Option<String>,
+    pub cached_at_unix: u64,
+    pub max_age_secs: Option<u64>,
+    pub no_store: bool,
+    #[serde(default)]
+    pub no_cache: bool,
+}
+
+/// Parsed `Cache-Control` directives relevant to revalidation.
+#[derive(Debug, Clone, Default)]
+pub struct CacheControlDirectives {
+    pub no_cache: bool,
+    pub no_store: bool,
+    pub max_age_secs: Option<u64>,
+}
+
+/// Path of the sidecar metadata file for a given downloaded file.
+pub fn sidecar_path(destination: &Path) -> PathBuf {
+    let mut path = destination.as_os_str().to_owned();
+    path.push(".xetcache.json");
+    PathBuf::from(path)
+}
+
+/// Load the sidecar metadata for `destination`, if present and well-formed.
+pub fn load(destination: &Path) -> Option<CachedFileMetadata> {
+    let bytes = std::fs::read(sidecar_path(destination)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Persist the sidecar metadata for `destination`, best-effort.
+pub fn save(destination: &Path, metadata: &CachedFileMetadata) {
+    if metadata.no_store {
+        let _ = std::fs::remove_file(sidecar_path(destination));
+        return;
+    }
+    if let Ok(bytes) = serde_json::to_vec_pretty(metadata) {
+        let _ = std::fs::write(sidecar_path(destination), bytes);
+    }
+}
+
+/// Build revalidation metadata from response headers, merging in the
+/// already-extracted XET hash (if any) so we can detect dedup changes.
+pub fn metadata_from_headers(headers: &HeaderMap, xet_hash: Option<&str>) -> CachedFileMetadata {
+    let directives = parse_cache_control(headers);
+
+    CachedFileMetadata {
+        etag: headers
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string()),
+        last_modified: headers
+            .get(reqwest::header::LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string()),
+        xet_hash: xet_hash.map(|s| s.to_string()),
+        cached_at_unix: now_unix(),
+        max_age_secs: directives.max_age_secs,
+        no_store: directives.no_store,
+        no_cache: directives.no_cache,
+    }
+}
+
+/// Parse the `Cache-Control` response header, if present.
+pub fn parse_cache_control(headers: &HeaderMap) -> CacheControlDirectives {
+    let mut directives = CacheControlDirectives::default();
+
+    let Some(raw) = headers
+        .get(reqwest::header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return directives;
+    };
+
+    for part in raw.split(',') {
+        let part = part.trim();
+        if part.eq_ignore_ascii_case("no-cache") {
+            directives.no_cache = true;
+        } else if part.eq_ignore_ascii_case("no-store") {
+            directives.no_store = true;
+        } else if let Some(value) = part.to_ascii_lowercase().strip_prefix("max-age=") {
+            directives.max_age_secs = value.trim().parse::<u64>().ok();
+        }
+    }
+
+    directives
+}
+
+/// Whether `metadata` is still within its freshness window.
+pub fn is_fresh(metadata: &CachedFileMetadata) -> bool {
+    if metadata.no_store || metadata.no_cache {
+        return false;
+    }
+    let Some(max_age) = metadata.max_age_secs else {
+        // No explicit freshness window: rely on conditional revalidation
+        // rather than trusting the cache blindly.
+        return false;
+    };
+    now_unix().saturating_sub(metadata.cached_at_unix) < max_age
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_max_age_and_no_cache() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            reqwest::header::CACHE_CONTROL,
+            "no-cache, max-age=120".parse().unwrap(),
+        );
+        let directives = parse_cache_control(&headers);
+        assert!(directives.no_cache);
+        assert!(!directives.no_store);
+        assert_eq!(directives.max_age_secs, Some(120));
+    }
+
+    #[test]
+    fn fresh_record_without_max_age_is_not_trusted() {
+        let metadata = CachedFileMetadata {
+            cached_at_unix: now_unix(),
+            ..Default::default()
+        };
+        assert!(!is_fresh(&metadata));
+    }
+
+    #[test]
+    fn fresh_record_within_window_is_trusted() {
+        let metadata = CachedFileMetadata {
+            cached_at_unix: now_unix(),
+            max_age_secs: Some(60),
+            ..Default::default()
+        };
+        assert!(is_fresh(&metadata));
+    }
+
+    #[test]
+    fn no_cache_forces_revalidation_even_within_window() {
+        let metadata = CachedFileMetadata {
+            cached_at_unix: now_unix(),
+            max_age_secs: Some(60),
+            no_cache: true,
+            ..Default::default()
+        };
+        assert!(!is_fresh(&metadata));
+    }
+}
@@ -2,17 +2,22 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use cas_client::remote_client::PREFIX_DEFAULT;
-use cas_client::{CacheConfig, FileProvider, OutputProvider, CHUNK_CACHE_SIZE_BYTES};
+use cas_client::{
+    CacheConfig, CompressionScheme, FileProvider, OutputProvider, CHUNK_CACHE_SIZE_BYTES,
+};
 use dirs::home_dir;
+use futures::stream::{self, StreamExt};
 use merklehash::MerkleHash;
 use progress_tracking::{
     item_tracking::ItemProgressUpdater, ProgressUpdate as TrackerProgressUpdate,
     TrackingProgressUpdater,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
-use tracing::{debug, info};
+use tokio::time::sleep;
+use tracing::{debug, info, Instrument};
 use ulid::Ulid;
 use utils::auth::{AuthConfig, TokenRefresher};
 use utils::errors::AuthError;
@@ -22,6 +27,7 @@ use xet_core_data::configurations::{
 };
 use xet_core_data::FileDownloader;
 
+use crate::logging::{log_download_completion, DownloadCompletionMetrics};
 use crate::progress::OperationProgress;
 use crate::xet_integration::{XetConnectionInfo, XetFileData, XetTokenManager};
 
@@ -30,13 +36,13 @@ use crate::xet_integration::{XetConnectionInfo, XetFileData, XetTokenManager};
 /// This implements the `TokenRefresher` trait required by xet-core's auth system.
 /// When the CAS client detects that a token is about to expire, it calls the
 /// `refresh()` method to obtain fresh credentials.
-struct HfTokenRefresher {
+pub(crate) struct HfTokenRefresher {
     token_manager: Arc<Mutex<XetTokenManager>>,
     file_data: XetFileData,
 }
 
 impl HfTokenRefresher {
-    fn new(token_manager: Arc<Mutex<XetTokenManager>>, file_data: XetFileData) -> Self {
+    pub(crate) fn new(token_manager: Arc<Mutex<XetTokenManager>>, file_data: XetFileData) -> Self {
         Self {
             token_manager,
             file_data,
@@ -73,11 +79,22 @@ impl TokenRefresher for HfTokenRefresher {
     }
 }
 
+/// A single file to fetch via [`XetDownloader::download_files`].
+#[derive(Debug, Clone)]
+pub struct DownloadItem {
+    pub file_hash: String,
+    pub destination_path: PathBuf,
+    pub file_name: String,
+    pub expected_size: u64,
+}
+
 /// XET Downloader that uses xet-core's FileDownloader for CAS operations
 pub struct XetDownloader {
     #[allow(dead_code)]
     config: Arc<TranslatorConfig>,
     downloader: Arc<FileDownloader>,
+    token_manager: Arc<Mutex<XetTokenManager>>,
+    file_data: XetFileData,
 }
 
 impl XetDownloader {
@@ -94,7 +111,7 @@ impl XetDownloader {
     ) -> Result<Self> {
         // Create a token refresher that will be called by xet-core when the token expires
         let refresher: Arc<dyn TokenRefresher> =
-            Arc::new(HfTokenRefresher::new(token_manager, file_data.clone()));
+            Arc::new(HfTokenRefresher::new(token_manager.clone(), file_data.clone()));
 
         let config = create_xet_config(
             connection_info.endpoint.clone(),
@@ -108,10 +125,24 @@ impl XetDownloader {
         let config = Arc::new(config);
         let downloader = Arc::new(FileDownloader::new(config.clone()).await?);
 
-        Ok(Self { config, downloader })
+        Ok(Self {
+            config,
+            downloader,
+            token_manager,
+            file_data: file_data.clone(),
+        })
     }
 
-    /// Download a file from XET CAS using its hash
+    /// Download a file from XET CAS using its hash, retrying transient
+    /// failures (including stalls) with exponential backoff and jitter.
+    /// Controlled by `XET_DOWNLOAD_MAX_RETRIES`, `XET_DOWNLOAD_IDLE_TIMEOUT_SECS`,
+    /// and `XET_DOWNLOAD_OVERALL_TIMEOUT_SECS` (see [`DownloadRetryConfig`]).
+    ///
+    /// Runs inside a `tracing` span scoped to this file so every log line
+    /// for its lifecycle (attempts, retries, completion) carries the same
+    /// `file`/`hash` fields, and — when `XET_LOG_METRICS=1` — emits a single
+    /// `download.complete` event with bytes/duration/retries on exit (see
+    /// [`crate::logging::log_download_completion`]).
     pub async fn download_file(
         &self,
         file_hash: &str,
@@ -119,6 +150,25 @@ impl XetDownloader {
         file_name: &str,
         expected_size: u64,
         progress: Option<OperationProgress>,
+    ) -> Result<u64> {
+        let span = tracing::info_span!(
+            "xet_download",
+            file = file_name,
+            hash = file_hash,
+            expected_size
+        );
+        self.download_file_inner(file_hash, destination_path, file_name, expected_size, progress)
+            .instrument(span)
+            .await
+    }
+
+    async fn download_file_inner(
+        &self,
+        file_hash: &str,
+        destination_path: &Path,
+        file_name: &str,
+        expected_size: u64,
+        progress: Option<OperationProgress>,
     ) -> Result<u64> {
         // Parse the hash string to MerkleHash
         let hash = MerkleHash::from_hex(file_hash)
@@ -129,11 +179,88 @@ impl XetDownloader {
             std::fs::create_dir_all(parent)?;
         }
 
+        let retry_config = DownloadRetryConfig::from_env();
+        let mut attempt = 0usize;
+        let started_at = Instant::now();
+
+        loop {
+            let result = self
+                .download_attempt(
+                    &hash,
+                    destination_path,
+                    file_name,
+                    expected_size,
+                    progress.as_ref().map(|p| p.clone_for_tasks()),
+                    &retry_config,
+                )
+                .await;
+
+            match result {
+                Ok(bytes) => {
+                    log_download_completion(&DownloadCompletionMetrics {
+                        file_name,
+                        bytes,
+                        duration: started_at.elapsed(),
+                        retries: attempt,
+                    });
+                    return Ok(bytes);
+                }
+                Err(err)
+                    if attempt + 1 < retry_config.max_attempts
+                        && (is_transient_xet_error(&err) || is_auth_error(&err)) =>
+                {
+                    attempt += 1;
+
+                    if is_auth_error(&err) {
+                        // Proactively re-resolve the connection info so the
+                        // next `refresh()` call xet-core makes against the
+                        // shared token manager picks up a fresh token
+                        // instead of re-deriving the one that just failed.
+                        let mut manager = self.token_manager.lock().await;
+                        let _ = manager.refresh_xet_connection_info(&self.file_data).await;
+                    }
+
+                    let backoff = xet_retry_backoff_with_jitter(attempt);
+                    debug!(
+                        "[RETRY] XET download of {} attempt {} failed: {err}; retrying in {:?}",
+                        file_name, attempt, backoff
+                    );
+                    sleep(backoff).await;
+                }
+                Err(err) => {
+                    log_download_completion(&DownloadCompletionMetrics {
+                        file_name,
+                        bytes: 0,
+                        duration: started_at.elapsed(),
+                        retries: attempt,
+                    });
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    async fn download_attempt(
+        &self,
+        hash: &MerkleHash,
+        destination_path: &Path,
+        file_name: &str,
+        expected_size: u64,
+        progress: Option<OperationProgress>,
+        retry_config: &DownloadRetryConfig,
+    ) -> Result<u64> {
         let output = OutputProvider::File(FileProvider::new(destination_path.to_path_buf()));
         let file_name_arc: Arc<str> = Arc::from(file_name.to_owned());
 
+        let last_activity = Arc::new(std::sync::Mutex::new(Instant::now()));
+        // Only a `ProgressBridge` bumps `last_activity`, so without a tracker
+        // the idle watchdog is skipped and only the overall deadline applies.
+        let idle_watch = progress.as_ref().map(|_| last_activity.clone());
         let progress_updater = progress.as_ref().map(|tracker| {
-            let bridge = Arc::new(ProgressBridge::new(tracker.clone_for_tasks()));
+            let bridge = Arc::new(
+                ProgressBridge::new(tracker.clone_for_tasks())
+                    .with_activity_tracker(last_activity.clone()),
+            );
             ItemProgressUpdater::new(bridge)
         });
 
@@ -141,10 +268,12 @@ impl XetDownloader {
             tracker.ensure_file_entry(file_name, expected_size);
         }
 
-        let bytes_downloaded = self
-            .downloader
-            .smudge_file_from_hash(&hash, file_name_arc, &output, None, progress_updater)
-            .await?;
+        let download_future =
+            self.downloader
+                .smudge_file_from_hash(hash, file_name_arc, &output, None, progress_updater);
+
+        let bytes_downloaded =
+            with_idle_and_overall_timeout(download_future, idle_watch, retry_config).await?;
 
         info!(
             "Downloaded {} bytes from XET CAS to {:?}",
@@ -153,21 +282,92 @@ impl XetDownloader {
 
         Ok(bytes_downloaded)
     }
+
+    /// Download many files concurrently over the shared `Arc<FileDownloader>`,
+    /// bounded by `max_concurrency`. A single file's failure does not abort
+    /// the batch; its slot in the returned `Vec` carries the error instead.
+    pub async fn download_files(
+        &self,
+        items: &[DownloadItem],
+        max_concurrency: usize,
+        progress: Option<OperationProgress>,
+    ) -> Vec<Result<u64>> {
+        let span = tracing::info_span!("xet_download_batch", files = items.len(), max_concurrency);
+        self.download_files_inner(items, max_concurrency, progress)
+            .instrument(span)
+            .await
+    }
+
+    async fn download_files_inner(
+        &self,
+        items: &[DownloadItem],
+        max_concurrency: usize,
+        progress: Option<OperationProgress>,
+    ) -> Vec<Result<u64>> {
+        if let Some(ref tracker) = progress {
+            for item in items {
+                tracker.ensure_file_entry(&item.file_name, item.expected_size);
+            }
+        }
+
+        let max_concurrency = max_concurrency.max(1).min(items.len().max(1));
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(max_concurrency));
+
+        let futures = items.iter().cloned().map(|item| {
+            let semaphore = semaphore.clone();
+            let progress = progress.as_ref().map(|p| p.clone_for_tasks());
+
+            async move {
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .context("download semaphore closed unexpectedly")?;
+
+                self.download_file(
+                    &item.file_hash,
+                    &item.destination_path,
+                    &item.file_name,
+                    item.expected_size,
+                    progress,
+                )
+                .await
+            }
+        });
+
+        stream::iter(futures)
+            .buffered(max_concurrency)
+            .collect()
+            .await
+    }
 }
 
-struct ProgressBridge {
+pub(crate) struct ProgressBridge {
     progress: OperationProgress,
+    /// Bumped on every `register_updates` call so a watchdog can detect a
+    /// stalled transfer (bytes not advancing) independent of wall-clock time.
+    activity_tracker: Option<Arc<std::sync::Mutex<Instant>>>,
 }
 
 impl ProgressBridge {
-    fn new(progress: OperationProgress) -> Self {
-        Self { progress }
+    pub(crate) fn new(progress: OperationProgress) -> Self {
+        Self {
+            progress,
+            activity_tracker: None,
+        }
+    }
+
+    pub(crate) fn with_activity_tracker(mut self, tracker: Arc<std::sync::Mutex<Instant>>) -> Self {
+        self.activity_tracker = Some(tracker);
+        self
     }
 }
 
 #[async_trait]
 impl TrackingProgressUpdater for ProgressBridge {
     async fn register_updates(&self, updates: TrackerProgressUpdate) {
+        if let Some(ref tracker) = self.activity_tracker {
+            *tracker.lock().unwrap() = Instant::now();
+        }
         self.progress.apply_tracking_update(&updates);
     }
 
@@ -176,8 +376,150 @@ impl TrackingProgressUpdater for ProgressBridge {
     }
 }
 
+/// Retry/timeout knobs for [`XetDownloader::download_file`], read once per
+/// attempt loop from the environment.
+struct DownloadRetryConfig {
+    max_attempts: usize,
+    idle_timeout: Duration,
+    overall_timeout: Option<Duration>,
+}
+
+impl DownloadRetryConfig {
+    fn from_env() -> Self {
+        let max_attempts = std::env::var("XET_DOWNLOAD_MAX_RETRIES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(XET_DOWNLOAD_DEFAULT_MAX_RETRIES);
+
+        let idle_timeout = std::env::var("XET_DOWNLOAD_IDLE_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(XET_DOWNLOAD_DEFAULT_IDLE_TIMEOUT);
+
+        let overall_timeout = std::env::var("XET_DOWNLOAD_OVERALL_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs);
+
+        Self {
+            max_attempts: max_attempts.max(1),
+            idle_timeout,
+            overall_timeout,
+        }
+    }
+}
+
+const XET_DOWNLOAD_DEFAULT_MAX_RETRIES: usize = 3;
+const XET_DOWNLOAD_DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const XET_RETRY_BACKOFF_BASE_MS: u64 = 250;
+const XET_RETRY_BACKOFF_MAX_MS: u64 = 8_000;
+/// How often the watchdog checks elapsed idle/overall time against `download_future`.
+const WATCHDOG_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Race `download_future` against an idle-time watchdog (reset by
+/// [`ProgressBridge::register_updates`] via `last_activity`; skipped when
+/// `last_activity` is `None`) and an optional overall deadline, failing the
+/// attempt if either trips before the future resolves.
+async fn with_idle_and_overall_timeout<F, T>(
+    download_future: F,
+    last_activity: Option<Arc<std::sync::Mutex<Instant>>>,
+    retry_config: &DownloadRetryConfig,
+) -> Result<T>
+where
+    F: std::future::Future<Output = Result<T>>,
+{
+    tokio::pin!(download_future);
+    let overall_deadline = retry_config.overall_timeout.map(|timeout| Instant::now() + timeout);
+
+    loop {
+        tokio::select! {
+            result = &mut download_future => return result,
+            _ = sleep(WATCHDOG_CHECK_INTERVAL) => {
+                if let Some(ref last_activity) = last_activity {
+                    let idle_for = last_activity.lock().unwrap().elapsed();
+                    if idle_for >= retry_config.idle_timeout {
+                        return Err(anyhow::anyhow!(
+                            "XET download stalled: no progress for {:?}",
+                            idle_for
+                        ));
+                    }
+                }
+                if let Some(deadline) = overall_deadline {
+                    if Instant::now() >= deadline {
+                        return Err(anyhow::anyhow!(
+                            "XET download exceeded overall timeout of {:?}",
+                            retry_config.overall_timeout.unwrap()
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Exponential backoff with full jitter, capped at `XET_RETRY_BACKOFF_MAX_MS`.
+fn xet_retry_backoff_with_jitter(attempt: usize) -> Duration {
+    let exp_ms = XET_RETRY_BACKOFF_BASE_MS.saturating_mul(1u64 << attempt.min(16));
+    let capped_ms = exp_ms.min(XET_RETRY_BACKOFF_MAX_MS);
+    let jittered_ms = xet_rand_below(capped_ms.max(1));
+    Duration::from_millis(jittered_ms)
+}
+
+/// Tiny dependency-free `[0, bound)` PRNG seeded from the system clock;
+/// sufficient for decorrelating retries, not for anything security-sensitive.
+fn xet_rand_below(bound: u64) -> u64 {
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    seed.wrapping_mul(6364136223846793005).wrapping_add(1) % bound
+}
+
+/// Whether `err` is worth retrying: network stalls/timeouts and transient
+/// 5xx-class CAS errors, but not a 4xx "hash not found" (permanent) failure.
+fn is_transient_xet_error(err: &anyhow::Error) -> bool {
+    let message = err.to_string().to_ascii_lowercase();
+    if message.contains("not found") || message.contains("404") || message.contains("400") {
+        return false;
+    }
+    message.contains("stalled")
+        || message.contains("timeout")
+        || message.contains("timed out")
+        || message.contains("connection")
+        || message.contains("500")
+        || message.contains("502")
+        || message.contains("503")
+        || message.contains("504")
+}
+
+/// Whether `err` looks like an expired/invalid credential rather than a
+/// plain connectivity failure, warranting a proactive token re-resolution
+/// before the next retry.
+fn is_auth_error(err: &anyhow::Error) -> bool {
+    let message = err.to_string().to_ascii_lowercase();
+    message.contains("401") || message.contains("403") || message.contains("unauthorized")
+}
+
+/// Select a transport compression scheme for CAS chunk payloads.
+///
+/// xet-core exposes no server-advertised capability to negotiate against
+/// here, so there is no safe way to auto-detect zstd support — enabling it
+/// against an endpoint that doesn't understand it risks a garbled or
+/// outright broken transfer. Rather than a plain `XET_COMPRESSION=zstd`
+/// toggle that looks like an ordinary, safe opt-in, the value must be the
+/// explicit `"zstd-unsafe-force"` (case-insensitive) to make that risk
+/// visible at the call site; anything else, including an unset or plain
+/// `"zstd"` value, conservatively falls back to no compression.
+fn compression_from_env() -> Option<CompressionScheme> {
+    match std::env::var("XET_COMPRESSION") {
+        Ok(value) if value.eq_ignore_ascii_case("zstd-unsafe-force") => Some(CompressionScheme::Zstd),
+        _ => None,
+    }
+}
+
 /// Create XET configuration compatible with xet-core
-fn create_xet_config(
+pub(crate) fn create_xet_config(
     endpoint: String,
     token_info: Option<(String, u64)>,
     token_refresher: Option<Arc<dyn TokenRefresher>>,
@@ -227,14 +569,14 @@ fn create_xet_config(
     Ok(TranslatorConfig {
         data_config: DataConfig {
             endpoint: Endpoint::Server(endpoint),
-            compression: None,
+            compression: compression_from_env(),
             auth: auth_cfg,
             prefix: PREFIX_DEFAULT.into(),
             cache_config: CacheConfig {
                 cache_directory: cache_path.join("chunk-cache"),
                 cache_size: *CHUNK_CACHE_SIZE_BYTES,
             },
-            staging_directory: None,
+            staging_directory: Some(staging_root.clone()),
         },
         shard_config: ShardConfig {
             prefix: PREFIX_DEFAULT.into(),
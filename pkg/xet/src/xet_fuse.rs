@@ -0,0 +1,657 @@
+// Read-only FUSE mount backed by XET CAS, for paging model weights in on
+// demand instead of pre-downloading a full snapshot.
+//
+// `FileDownloader` only exposes whole-file smudging (`smudge_file_from_hash`
+// into an `OutputProvider`), not a byte-range CAS fetch, so `read()` does not
+// pull individual covering chunks directly. Instead each inode is smudged
+// into a local cache file at most once, on first `open()` (shared across
+// concurrent opens of the same inode), and subsequent reads are served from
+// that cache file. This still defers network I/O to files that are actually
+// touched, which is the property inference servers mmap-ing large
+// safetensors shards care about; it falls short of sub-file range laziness,
+// which would need a chunk-addressable read path xet-core does not expose
+// here.
+//
+// `RepoFuse`/`RepoFuseMount` below mount an arbitrary `XetClient` repository
+// revision lazily too, materializing each file on its first `read()`, driven by
+// `list_files`/`download_file_with_options` instead of a precomputed CAS
+// manifest, so it works against any backend the client supports (object
+// stores included), not just XET CAS.
+#![cfg(feature = "fuse")]
+
+use crate::hf_adapter::HfFileInfo;
+use crate::progress::OperationProgress;
+use crate::xet_downloader::XetDownloader;
+use crate::{DownloadOptions, OperationContext, XetClient};
+use anyhow::{anyhow, Result};
+use fuser::{
+    FileAttr, FileType, Filesystem, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry, ReplyOpen,
+    Request,
+};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+const ATTR_TTL: Duration = Duration::from_secs(60);
+const ROOT_INODE: u64 = 1;
+
+/// `FileAttr` for a regular file inode of `size` bytes, read-only.
+fn file_attr(ino: u64, size: u64) -> FileAttr {
+    let now = SystemTime::now();
+    FileAttr {
+        ino,
+        size,
+        blocks: size.div_ceil(512),
+        atime: now,
+        mtime: now,
+        ctime: now,
+        crtime: now,
+        kind: FileType::RegularFile,
+        perm: 0o444,
+        nlink: 1,
+        uid: 0,
+        gid: 0,
+        rdev: 0,
+        blksize: 4096,
+        flags: 0,
+    }
+}
+
+/// `FileAttr` for the read-only root directory every mount is rooted at.
+fn root_attr() -> FileAttr {
+    let now = SystemTime::now();
+    FileAttr {
+        ino: ROOT_INODE,
+        size: 0,
+        blocks: 0,
+        atime: now,
+        mtime: now,
+        ctime: now,
+        crtime: now,
+        kind: FileType::Directory,
+        perm: 0o555,
+        nlink: 2,
+        uid: 0,
+        gid: 0,
+        rdev: 0,
+        blksize: 4096,
+        flags: 0,
+    }
+}
+
+/// One entry in the manifest mounted as a read-only filesystem.
+#[derive(Debug, Clone)]
+pub struct FileManifestEntry {
+    pub path: String,
+    pub file_hash: String,
+    pub size: u64,
+}
+
+/// Marker error signaling that another caller's fetch for this inode is
+/// already in flight, so the FUSE kernel driver should retry the op rather
+/// than treat it as a hard failure. Distinguished via `downcast_ref` at the
+/// `open`/`read` call sites (see [`fetch_errno`]) so the in-flight case maps
+/// to `EAGAIN` while a genuine download failure still maps to `EIO`.
+#[derive(Debug)]
+struct FetchInProgress;
+
+impl std::fmt::Display for FetchInProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "fetch already in progress")
+    }
+}
+
+impl std::error::Error for FetchInProgress {}
+
+/// Map an `ensure_fetched` failure to the errno reported back to the FUSE
+/// kernel driver: `EAGAIN` for an in-flight fetch (safe to retry once the
+/// first caller finishes), `EIO` for everything else.
+fn fetch_errno(err: &anyhow::Error) -> i32 {
+    if err.downcast_ref::<FetchInProgress>().is_some() {
+        libc::EAGAIN
+    } else {
+        libc::EIO
+    }
+}
+
+enum FetchState {
+    NotFetched,
+    Fetching,
+    Ready,
+    Failed(String),
+}
+
+struct Inode {
+    entry: FileManifestEntry,
+    cache_path: PathBuf,
+    state: Mutex<FetchState>,
+}
+
+/// `fuser::Filesystem` implementation serving a flat manifest of files as a
+/// read-only tree rooted at the mountpoint.
+struct XetFuse {
+    downloader: Arc<XetDownloader>,
+    inodes: Vec<Arc<Inode>>,
+    path_to_ino: HashMap<String, u64>,
+}
+
+impl XetFuse {
+    fn new(manifest: Vec<FileManifestEntry>, downloader: Arc<XetDownloader>, cache_dir: &Path) -> Self {
+        let mut path_to_ino = HashMap::with_capacity(manifest.len());
+        let inodes = manifest
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                // Inode numbers start at 2; 1 is reserved for the root directory.
+                let ino = index as u64 + 2;
+                path_to_ino.insert(entry.path.clone(), ino);
+                Arc::new(Inode {
+                    cache_path: cache_dir.join(format!("{ino:016x}.bin")),
+                    entry,
+                    state: Mutex::new(FetchState::NotFetched),
+                })
+            })
+            .collect();
+
+        Self {
+            downloader,
+            inodes,
+            path_to_ino,
+        }
+    }
+
+    fn inode(&self, ino: u64) -> Option<&Arc<Inode>> {
+        (ino >= 2)
+            .then(|| self.inodes.get((ino - 2) as usize))
+            .flatten()
+    }
+
+    /// Smudge `inode`'s file into its cache slot if this is the first access,
+    /// otherwise return the outcome of the earlier fetch, or `FetchInProgress`
+    /// while that fetch is still running.
+    fn ensure_fetched(&self, inode: &Arc<Inode>) -> Result<()> {
+        {
+            let mut state = inode.state.lock().unwrap();
+            match &*state {
+                FetchState::Ready => return Ok(()),
+                FetchState::Failed(err) => return Err(anyhow!(err.clone())),
+                FetchState::Fetching => return Err(anyhow!(FetchInProgress)),
+                FetchState::NotFetched => *state = FetchState::Fetching,
+            }
+        }
+
+        // Only the caller that transitioned `NotFetched -> Fetching` reaches
+        // here and performs the download; every other caller already
+        // returned `FetchInProgress` above, which `fetch_errno` reports to
+        // the FUSE caller as `EAGAIN`.
+        let downloader = self.downloader.clone();
+        let entry = inode.entry.clone();
+        let cache_path = inode.cache_path.clone();
+
+        let result = crate::runtime::block_on(async move {
+            downloader
+                .download_file(&entry.file_hash, &cache_path, &entry.path, entry.size, None)
+                .await
+        });
+
+        let mut state = inode.state.lock().unwrap();
+        match result {
+            Ok(_) => {
+                *state = FetchState::Ready;
+                Ok(())
+            }
+            Err(err) => {
+                let message = err.to_string();
+                *state = FetchState::Failed(message.clone());
+                Err(anyhow!(message))
+            }
+        }
+    }
+}
+
+impl Filesystem for XetFuse {
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        if parent != ROOT_INODE {
+            reply.error(libc::ENOENT);
+            return;
+        }
+
+        let Some(name) = name.to_str() else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        let Some(&ino) = self.path_to_ino.get(name) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self.inode(ino) {
+            Some(inode) => reply.entry(&ATTR_TTL, &file_attr(ino, inode.entry.size), 0),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, ino: u64, _fh: Option<u64>, reply: ReplyAttr) {
+        if ino == ROOT_INODE {
+            reply.attr(&ATTR_TTL, &root_attr());
+            return;
+        }
+
+        match self.inode(ino) {
+            Some(inode) => reply.attr(&ATTR_TTL, &file_attr(ino, inode.entry.size)),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn open(&mut self, _req: &Request<'_>, ino: u64, _flags: i32, reply: ReplyOpen) {
+        match self.inode(ino) {
+            Some(inode) => match self.ensure_fetched(inode) {
+                Ok(()) => reply.opened(0, 0),
+                Err(err) => reply.error(fetch_errno(&err)),
+            },
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let Some(inode) = self.inode(ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        if let Err(err) = self.ensure_fetched(inode) {
+            reply.error(fetch_errno(&err));
+            return;
+        }
+
+        match std::fs::read(&inode.cache_path) {
+            Ok(bytes) => {
+                let offset = offset.max(0) as usize;
+                let end = (offset + size as usize).min(bytes.len());
+                let slice = if offset >= bytes.len() { &[] } else { &bytes[offset..end] };
+                reply.data(slice);
+            }
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn release(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        _fh: u64,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        _flush: bool,
+        reply: fuser::ReplyEmpty,
+    ) {
+        reply.ok();
+    }
+}
+
+/// Handle for an active mount; unmounts when dropped.
+pub struct XetFuseMount {
+    _session: fuser::BackgroundSession,
+}
+
+impl XetFuseMount {
+    /// Mount `manifest` as a read-only filesystem at `mountpoint`, smudging
+    /// each file from CAS via `downloader` lazily on first access. `cache_dir`
+    /// holds the per-inode materialized files backing reads.
+    pub fn mount(
+        manifest: Vec<FileManifestEntry>,
+        mountpoint: &Path,
+        downloader: Arc<XetDownloader>,
+        cache_dir: &Path,
+    ) -> Result<Self> {
+        std::fs::create_dir_all(cache_dir)?;
+        let fs = XetFuse::new(manifest, downloader, cache_dir);
+        let session = fuser::spawn_mount2(fs, mountpoint, &[fuser::MountOption::RO])?;
+        Ok(Self { _session: session })
+    }
+}
+
+enum RepoFetchState {
+    NotFetched,
+    Fetching,
+    Ready(PathBuf),
+    Failed(String),
+}
+
+struct RepoInode {
+    info: HfFileInfo,
+    state: Mutex<RepoFetchState>,
+}
+
+/// `fuser::Filesystem` backed by an arbitrary [`XetClient`] repository
+/// revision instead of a precomputed XET CAS manifest: `readdir`/`getattr`
+/// are served from `list_files`'s cached result, and `read` triggers
+/// `XetClient::download_file_with_options` on first access (the same
+/// whole-file materialization every other caller of that method gets — see
+/// the module doc comment on why this falls short of sub-file range
+/// laziness).
+struct RepoFuse {
+    client: XetClient,
+    repo_id: String,
+    repo_type: Option<String>,
+    revision: String,
+    cache_dir: PathBuf,
+    cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+    progress: Option<OperationProgress>,
+    inodes: Vec<Arc<RepoInode>>,
+    path_to_ino: HashMap<String, u64>,
+}
+
+impl RepoFuse {
+    fn new(
+        files: Vec<HfFileInfo>,
+        client: XetClient,
+        repo_id: String,
+        repo_type: Option<String>,
+        revision: String,
+        cache_dir: PathBuf,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Self {
+        let mut path_to_ino = HashMap::with_capacity(files.len());
+        let inodes = files
+            .into_iter()
+            .enumerate()
+            .map(|(index, info)| {
+                let ino = index as u64 + 2;
+                path_to_ino.insert(info.path.clone(), ino);
+                Arc::new(RepoInode {
+                    info,
+                    state: Mutex::new(RepoFetchState::NotFetched),
+                })
+            })
+            .collect();
+
+        Self {
+            client,
+            repo_id,
+            repo_type,
+            revision,
+            cache_dir,
+            cancel_check,
+            progress,
+            inodes,
+            path_to_ino,
+        }
+    }
+
+    fn inode(&self, ino: u64) -> Option<&Arc<RepoInode>> {
+        (ino >= 2)
+            .then(|| self.inodes.get((ino - 2) as usize))
+            .flatten()
+    }
+
+    /// Download `inode`'s file into `cache_dir` if this is the first access,
+    /// otherwise return the outcome of the earlier fetch, or `FetchInProgress`
+    /// while that fetch is still running.
+    fn ensure_fetched(&self, inode: &Arc<RepoInode>) -> Result<PathBuf> {
+        {
+            let mut state = inode.state.lock().unwrap();
+            match &*state {
+                RepoFetchState::Ready(path) => return Ok(path.clone()),
+                RepoFetchState::Failed(err) => return Err(anyhow!(err.clone())),
+                RepoFetchState::Fetching => return Err(anyhow!(FetchInProgress)),
+                RepoFetchState::NotFetched => *state = RepoFetchState::Fetching,
+            }
+        }
+
+        // Only the caller that transitioned `NotFetched -> Fetching` reaches
+        // here and performs the download; every other caller already
+        // returned `FetchInProgress` above, which `fetch_errno` reports to
+        // the FUSE caller as `EAGAIN`.
+        let client = self.client.clone();
+        let repo_id = self.repo_id.clone();
+        let repo_type = self.repo_type.clone();
+        let revision = self.revision.clone();
+        let local_dir = self.cache_dir.to_string_lossy().to_string();
+        let remote_path = inode.info.path.clone();
+        let cancel_check = self.cancel_check.clone();
+        let progress = self.progress.as_ref().map(|p| p.clone_for_tasks());
+
+        let result = crate::runtime::block_on(async move {
+            client
+                .download_file_with_options(
+                    &repo_id,
+                    &remote_path,
+                    DownloadOptions {
+                        repo_type: repo_type.as_deref(),
+                        revision: Some(&revision),
+                        local_dir: Some(&local_dir),
+                        force_revalidate: false,
+                        resume: false,
+                    },
+                    OperationContext::new(cancel_check, progress),
+                )
+                .await
+        });
+
+        let mut state = inode.state.lock().unwrap();
+        match result {
+            Ok(path) => {
+                let path = PathBuf::from(path);
+                *state = RepoFetchState::Ready(path.clone());
+                Ok(path)
+            }
+            Err(err) => {
+                let message = err.to_string();
+                *state = RepoFetchState::Failed(message.clone());
+                Err(anyhow!(message))
+            }
+        }
+    }
+}
+
+impl Filesystem for RepoFuse {
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        if parent != ROOT_INODE {
+            reply.error(libc::ENOENT);
+            return;
+        }
+
+        let Some(name) = name.to_str() else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        let Some(&ino) = self.path_to_ino.get(name) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self.inode(ino) {
+            Some(inode) => reply.entry(&ATTR_TTL, &file_attr(ino, inode.info.size), 0),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, ino: u64, _fh: Option<u64>, reply: ReplyAttr) {
+        if ino == ROOT_INODE {
+            reply.attr(&ATTR_TTL, &root_attr());
+            return;
+        }
+
+        match self.inode(ino) {
+            Some(inode) => reply.attr(&ATTR_TTL, &file_attr(ino, inode.info.size)),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn open(&mut self, _req: &Request<'_>, ino: u64, _flags: i32, reply: ReplyOpen) {
+        match self.inode(ino) {
+            // The download is deferred to the file's first `read`.
+            Some(_) => reply.opened(0, 0),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        if ino != ROOT_INODE {
+            reply.error(libc::ENOENT);
+            return;
+        }
+
+        let entries = std::iter::once((ROOT_INODE, FileType::Directory, ".".to_string()))
+            .chain(std::iter::once((ROOT_INODE, FileType::Directory, "..".to_string())))
+            .chain(
+                self.path_to_ino
+                    .iter()
+                    .map(|(path, &ino)| (ino, FileType::RegularFile, path.clone())),
+            );
+
+        for (i, (ino, kind, name)) in entries.enumerate().skip(offset as usize) {
+            if reply.add(ino, (i + 1) as i64, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let Some(inode) = self.inode(ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        let cache_path = match self.ensure_fetched(inode) {
+            Ok(path) => path,
+            Err(err) => {
+                reply.error(fetch_errno(&err));
+                return;
+            }
+        };
+
+        match std::fs::read(&cache_path) {
+            Ok(bytes) => {
+                let offset = offset.max(0) as usize;
+                let end = (offset + size as usize).min(bytes.len());
+                let slice = if offset >= bytes.len() { &[] } else { &bytes[offset..end] };
+                reply.data(slice);
+            }
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn release(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        _fh: u64,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        _flush: bool,
+        reply: fuser::ReplyEmpty,
+    ) {
+        reply.ok();
+    }
+}
+
+/// Handle for an active `RepoFuse` mount; unmounts when dropped (either
+/// directly, or because the cancellation watcher thread already took the
+/// session and dropped it). Dropping this handle also stops that watcher
+/// thread if cancellation never fired.
+pub struct RepoFuseMount {
+    session: Arc<Mutex<Option<fuser::BackgroundSession>>>,
+    watcher_stop: Arc<AtomicBool>,
+}
+
+impl RepoFuseMount {
+    /// Mount `repo_id`'s `revision` as a read-only filesystem at
+    /// `mountpoint`: `readdir`/`getattr` are served from one `list_files`
+    /// call made up front, and each file's content is downloaded into
+    /// `cache_dir` lazily on first `read`. If `cancel_check` is provided, a
+    /// background thread polls it and unmounts (by dropping the underlying
+    /// `BackgroundSession`) as soon as it reports cancellation.
+    #[allow(clippy::too_many_arguments)]
+    pub fn mount(
+        client: XetClient,
+        repo_id: String,
+        repo_type: Option<String>,
+        revision: Option<String>,
+        mountpoint: &Path,
+        cache_dir: &Path,
+        cancel_check: Option<Arc<dyn Fn() -> bool + Send + Sync>>,
+        progress: Option<OperationProgress>,
+    ) -> Result<Self> {
+        let revision = revision.unwrap_or_else(|| "main".to_string());
+        std::fs::create_dir_all(cache_dir)?;
+
+        let files = crate::runtime::block_on(client.list_files(&repo_id, Some(&revision)))?;
+
+        let fs = RepoFuse::new(
+            files,
+            client,
+            repo_id,
+            repo_type,
+            revision,
+            cache_dir.to_path_buf(),
+            cancel_check.clone(),
+            progress,
+        );
+        let session = fuser::spawn_mount2(fs, mountpoint, &[fuser::MountOption::RO])?;
+        let session = Arc::new(Mutex::new(Some(session)));
+
+        let watcher_stop = Arc::new(AtomicBool::new(false));
+        if let Some(cancel_check) = cancel_check {
+            let watcher_stop = watcher_stop.clone();
+            let session = session.clone();
+            std::thread::spawn(move || {
+                while !watcher_stop.load(Ordering::Relaxed) {
+                    if cancel_check() {
+                        // Dropping the `BackgroundSession` unmounts it.
+                        session.lock().unwrap().take();
+                        break;
+                    }
+                    std::thread::sleep(Duration::from_millis(200));
+                }
+            });
+        }
+
+        Ok(Self {
+            session,
+            watcher_stop,
+        })
+    }
+}
+
+impl Drop for RepoFuseMount {
+    fn drop(&mut self) {
+        self.watcher_stop.store(true, Ordering::Relaxed);
+        self.session.lock().unwrap().take();
+    }
+}
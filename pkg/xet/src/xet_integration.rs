@@ -1,5 +1,9 @@
+use crate::credentials::{host_of, CredentialProvider};
+use crate::http_config::HttpClientConfig;
+use crate::network_policy::NetworkPolicy;
 use anyhow::{anyhow, Context, Result};
 use reqwest::header::{HeaderMap, HeaderValue};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// XET file metadata extracted from HuggingFace API responses
@@ -19,13 +23,12 @@ pub struct XetConnectionInfo {
 
 /// Token type for XET operations
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
 pub enum XetTokenType {
+    #[allow(dead_code)]
     Read,
     Write,
 }
 
-#[allow(dead_code)]
 impl std::fmt::Display for XetTokenType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -114,11 +117,18 @@ pub struct XetTokenManager {
     client: reqwest::Client,
     #[allow(dead_code)]
     hf_token: Option<String>,
+    credential_provider: Option<Arc<dyn CredentialProvider>>,
+    network_policy: Option<Arc<NetworkPolicy>>,
     cached_connection_info: Option<(XetConnectionInfo, String)>, // (info, refresh_route)
 }
 
 impl XetTokenManager {
-    pub fn new(hf_token: Option<String>) -> Self {
+    pub fn new(
+        hf_token: Option<String>,
+        credential_provider: Option<Arc<dyn CredentialProvider>>,
+        http_config: HttpClientConfig,
+        network_policy: Option<Arc<NetworkPolicy>>,
+    ) -> Self {
         let mut headers = HeaderMap::new();
         if let Some(ref token) = hf_token {
             if let Ok(header_value) = HeaderValue::from_str(&format!("Bearer {}", token)) {
@@ -126,18 +136,33 @@ impl XetTokenManager {
             }
         }
 
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
-            .build()
+        let client = http_config
+            .merged_headers(headers)
+            .and_then(|headers| {
+                http_config.apply(reqwest::Client::builder().default_headers(headers))
+            })
+            .and_then(|builder| builder.build().map_err(anyhow::Error::from))
             .unwrap_or_default();
 
         Self {
             client,
             hf_token,
+            credential_provider,
+            network_policy,
             cached_connection_info: None,
         }
     }
 
+    /// Resolve the `Authorization` header to use for `url`, preferring a
+    /// per-host credential from the provider over the default client header.
+    fn auth_header_for(&self, url: &str) -> Option<String> {
+        let provider = self.credential_provider.as_ref()?;
+        let host = host_of(url)?;
+        provider
+            .credentials_for_host(&host)
+            .and_then(|credential| credential.to_header_value())
+    }
+
     /// Check if the cached token is still valid
     fn is_token_valid(&self) -> bool {
         if let Some((ref info, _)) = self.cached_connection_info {
@@ -167,9 +192,16 @@ impl XetTokenManager {
             }
         }
 
-        let response = self
-            .client
-            .get(&file_data.refresh_route)
+        if let Some(ref policy) = self.network_policy {
+            policy.check_url(&file_data.refresh_route)?;
+        }
+
+        let mut request = self.client.get(&file_data.refresh_route);
+        if let Some(auth) = self.auth_header_for(&file_data.refresh_route) {
+            request = request.header(reqwest::header::AUTHORIZATION, auth);
+        }
+
+        let response = request
             .send()
             .await
             .context("Failed to fetch XET connection info")?;
@@ -185,6 +217,13 @@ impl XetTokenManager {
         let connection_info = parse_xet_connection_info_from_headers(headers)
             .ok_or_else(|| anyhow!("XET headers not found in response"))?;
 
+        // The CAS endpoint is itself a server-controlled redirect (the
+        // `x-xet-cas-url` response header), so it must pass the same policy
+        // check as the refresh route that produced it.
+        if let Some(ref policy) = self.network_policy {
+            policy.check_url(&connection_info.endpoint)?;
+        }
+
         // Cache the connection info
         self.cached_connection_info =
             Some((connection_info.clone(), file_data.refresh_route.clone()));
@@ -193,7 +232,6 @@ impl XetTokenManager {
     }
 
     /// Fetch XET connection info directly from repo info
-    #[allow(dead_code)]
     pub async fn fetch_xet_connection_info_from_repo(
         &mut self,
         token_type: XetTokenType,
@@ -208,9 +246,16 @@ impl XetTokenManager {
             endpoint, repo_type, repo_id, token_type, revision
         );
 
-        let response = self
-            .client
-            .get(&url)
+        if let Some(ref policy) = self.network_policy {
+            policy.check_url(&url)?;
+        }
+
+        let mut request = self.client.get(&url);
+        if let Some(auth) = self.auth_header_for(&url) {
+            request = request.header(reqwest::header::AUTHORIZATION, auth);
+        }
+
+        let response = request
             .send()
             .await
             .context("Failed to fetch XET token")?;
@@ -226,6 +271,10 @@ impl XetTokenManager {
         let connection_info = parse_xet_connection_info_from_headers(headers)
             .ok_or_else(|| anyhow!("XET headers not found in response"))?;
 
+        if let Some(ref policy) = self.network_policy {
+            policy.check_url(&connection_info.endpoint)?;
+        }
+
         Ok(connection_info)
     }
 }
@@ -0,0 +1,96 @@
+// XET Core integration using FileUploader for CAS ingest.
+//
+// Mirrors `xet_downloader.rs`: the same `create_xet_config`/`HfTokenRefresher`
+// plumbing and `ProgressBridge` reporting, but drives xet-core's upload path
+// instead of `FileDownloader`. Content-defined chunking and dedup against
+// already-known shards happen inside `FileUploader::upload_file` itself —
+// only chunks not already present in CAS are actually streamed.
+use anyhow::{Context, Result};
+use merklehash::MerkleHash;
+use progress_tracking::item_tracking::ItemProgressUpdater;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+use utils::auth::TokenRefresher;
+use xet_core_data::configurations::TranslatorConfig;
+use xet_core_data::FileUploader;
+
+use crate::progress::OperationProgress;
+use crate::xet_downloader::{create_xet_config, HfTokenRefresher, ProgressBridge};
+use crate::xet_integration::{XetConnectionInfo, XetFileData, XetTokenManager};
+
+/// XET uploader that uses xet-core's `FileUploader` to clean and push local
+/// files into CAS.
+pub struct XetUploader {
+    #[allow(dead_code)]
+    config: Arc<TranslatorConfig>,
+    uploader: Arc<FileUploader>,
+}
+
+impl XetUploader {
+    /// Create a new XET uploader against the same connection info/refresh
+    /// route a downloader for this repo would use.
+    pub async fn new(
+        connection_info: &XetConnectionInfo,
+        file_data: &XetFileData,
+        token_manager: Arc<Mutex<XetTokenManager>>,
+    ) -> Result<Self> {
+        let refresher: Arc<dyn TokenRefresher> =
+            Arc::new(HfTokenRefresher::new(token_manager, file_data.clone()));
+
+        let config = create_xet_config(
+            connection_info.endpoint.clone(),
+            Some((
+                connection_info.access_token.clone(),
+                connection_info.expiration_unix_epoch,
+            )),
+            Some(refresher),
+        )?;
+
+        let config = Arc::new(config);
+        let uploader = Arc::new(FileUploader::new(config.clone()).await?);
+
+        Ok(Self { config, uploader })
+    }
+
+    /// Clean, chunk, dedup, and upload `source_path` to CAS, returning the
+    /// resulting file's `MerkleHash` so the caller can register the
+    /// uploaded artifact (e.g. commit it to the HF API).
+    pub async fn upload_file(
+        &self,
+        source_path: &Path,
+        file_name: &str,
+        progress: Option<OperationProgress>,
+    ) -> Result<MerkleHash> {
+        let data = tokio::fs::read(source_path)
+            .await
+            .with_context(|| format!("failed to read {source_path:?} for upload"))?;
+
+        let progress_updater = progress.as_ref().map(|tracker| {
+            let bridge = Arc::new(ProgressBridge::new(tracker.clone_for_tasks()));
+            ItemProgressUpdater::new(bridge)
+        });
+
+        if let Some(ref tracker) = progress {
+            tracker.ensure_file_entry(file_name, data.len() as u64);
+        }
+
+        let size = data.len() as u64;
+        let file_name_arc: Arc<str> = Arc::from(file_name.to_owned());
+        let hash = self
+            .uploader
+            .upload_file(file_name_arc, data, progress_updater)
+            .await
+            .with_context(|| format!("failed to upload {file_name}"))?;
+
+        if let Some(ref tracker) = progress {
+            tracker.update_file_absolute(file_name, size, size, true);
+        }
+
+        debug!("[XET] uploaded {file_name} as {}", hash.hex());
+        info!("[XET] upload complete for {file_name}: {}", hash.hex());
+
+        Ok(hash)
+    }
+}